@@ -0,0 +1,171 @@
+//! Attack-decay-sustain-release envelope.
+
+use crate::clock::Time;
+
+/// ADSR envelope producing a level 0.0..1.0 over time.
+///
+/// The envelope is driven by `process()`, which must be called with the current
+/// time. `gate_on()` and `gate_off()` take effect on the next `process()`.
+#[derive(Debug, Clone)]
+pub struct Adsr<const FQ: u32> {
+    /// Time to go from the current level to 1.0.
+    pub attack: Time<FQ>,
+    /// Time to go from 1.0 to the sustain level.
+    pub decay: Time<FQ>,
+    /// Level 0.0..1.0 to hold while the gate is on.
+    pub sustain: f32,
+    /// Time to go from the current level to 0.0 after the gate is off.
+    pub release: Time<FQ>,
+
+    /// Current stage of the envelope.
+    stage: Stage,
+    /// When the current stage started. None until the next `process()`.
+    since: Option<Time<FQ>>,
+    /// Level when the current stage started.
+    start_level: f32,
+    /// Last calculated level.
+    level: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+impl<const FQ: u32> Adsr<FQ> {
+    pub fn new(attack: Time<FQ>, decay: Time<FQ>, sustain: f32, release: Time<FQ>) -> Self {
+        Adsr {
+            attack,
+            decay,
+            sustain,
+            release,
+            stage: Stage::Idle,
+            since: None,
+            start_level: 0.0,
+            level: 0.0,
+        }
+    }
+
+    /// Start the envelope. If it is already running (like in the release stage), the
+    /// attack restarts from the current level.
+    pub fn gate_on(&mut self) {
+        self.set_stage(Stage::Attack);
+    }
+
+    /// Release the envelope from whatever level it is at.
+    pub fn gate_off(&mut self) {
+        if self.stage != Stage::Idle {
+            self.set_stage(Stage::Release);
+        }
+    }
+
+    /// Update the envelope and get the current level 0.0..1.0.
+    pub fn process(&mut self, now: Time<FQ>) -> f32 {
+        // Loop since a stage might be over already, in which case we continue
+        // with the next one. Zero-length stages are passed instantly.
+        loop {
+            let since = *self.since.get_or_insert(now);
+            let elapsed = now - since;
+
+            let (length, target, next) = match self.stage {
+                Stage::Idle => {
+                    self.level = 0.0;
+                    break;
+                }
+                Stage::Attack => (self.attack, 1.0, Stage::Decay),
+                Stage::Decay => (self.decay, self.sustain, Stage::Sustain),
+                Stage::Sustain => {
+                    self.level = self.sustain;
+                    break;
+                }
+                Stage::Release => (self.release, 0.0, Stage::Idle),
+            };
+
+            if elapsed >= length {
+                self.stage = next;
+                self.since = Some(since + length);
+                self.start_level = target;
+                self.level = target;
+                continue;
+            }
+
+            // weight along this stage 0.0..1.0
+            let w = elapsed.count() as f32 / length.count() as f32;
+
+            self.level = self.start_level + (target - self.start_level) * w;
+            break;
+        }
+
+        self.level
+    }
+
+    fn set_stage(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.since = None;
+        self.start_level = self.level;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(millis: i64) -> Time<1000> {
+        Time::from_millis(millis)
+    }
+
+    #[test]
+    fn test_adsr_cycle() {
+        let mut env = Adsr::new(ms(100), ms(100), 0.5, ms(200));
+
+        assert_eq!(env.process(ms(0)), 0.0);
+
+        env.gate_on();
+        assert_eq!(env.process(ms(0)), 0.0);
+        assert_eq!(env.process(ms(50)), 0.5);
+        assert_eq!(env.process(ms(100)), 1.0);
+        assert_eq!(env.process(ms(150)), 0.75);
+        assert_eq!(env.process(ms(200)), 0.5);
+        assert_eq!(env.process(ms(1000)), 0.5);
+
+        env.gate_off();
+        assert_eq!(env.process(ms(1000)), 0.5);
+        assert_eq!(env.process(ms(1100)), 0.25);
+        assert_eq!(env.process(ms(1200)), 0.0);
+        assert_eq!(env.process(ms(1300)), 0.0);
+    }
+
+    #[test]
+    fn test_adsr_zero_length() {
+        let mut env = Adsr::new(ms(0), ms(0), 0.5, ms(0));
+
+        env.gate_on();
+        assert_eq!(env.process(ms(0)), 0.5);
+
+        env.gate_off();
+        assert_eq!(env.process(ms(10)), 0.0);
+    }
+
+    #[test]
+    fn test_adsr_retrigger_in_release() {
+        let mut env = Adsr::new(ms(100), ms(100), 0.5, ms(200));
+
+        env.gate_on();
+        env.process(ms(0));
+        env.process(ms(200));
+
+        env.gate_off();
+        env.process(ms(200));
+        assert_eq!(env.process(ms(300)), 0.25);
+
+        // Restart attack from 0.25.
+        env.gate_on();
+        assert_eq!(env.process(ms(300)), 0.25);
+        assert_eq!(env.process(ms(350)), 0.625);
+        assert_eq!(env.process(ms(400)), 1.0);
+    }
+}
@@ -9,8 +9,29 @@ pub trait Delay: Sized + Default {
     /// Read the current index
     fn read(&self) -> f32;
 
+    /// Read the sample written `samples_back` writes ago, where 0 is the most recently
+    /// written. Wraps around the sample count, which means `read_at(sample_count - 1)`
+    /// is the same as `read()`.
+    fn read_at(&self, samples_back: usize) -> f32;
+
+    /// Like `read_at()`, but with a fractional position linearly interpolated between
+    /// the two adjacent samples.
+    fn read_frac(&self, samples_back: f32) -> f32 {
+        let samples_back = samples_back.max(0.0);
+
+        let n = samples_back as usize;
+        let w = samples_back - n as f32;
+
+        let (a, b) = (self.read_at(n), self.read_at(n + 1));
+
+        a + (b - a) * w
+    }
+
     /// Write the current index and move to next sample
     fn write(&mut self, v: f32);
+
+    /// Zero all samples and start over from the first index. Keeps the sample count.
+    fn clear(&mut self);
 }
 
 /// An in-memory version of the [`Delay`] trait
@@ -30,6 +51,13 @@ impl<const N: usize> Default for MemoryDelay<N> {
     }
 }
 
+impl<const N: usize> MemoryDelay<N> {
+    /// The amount of samples in the delay
+    pub fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+}
+
 impl<const N: usize> Delay for MemoryDelay<N> {
     fn set_sample_count(&mut self, sample_count: usize) {
         if sample_count > N {
@@ -43,6 +71,13 @@ impl<const N: usize> Delay for MemoryDelay<N> {
         self.buffer[self.index]
     }
 
+    fn read_at(&self, samples_back: usize) -> f32 {
+        let back = samples_back % self.sample_count + 1;
+        let index = (self.index + self.sample_count - back) % self.sample_count;
+
+        self.buffer[index]
+    }
+
     fn write(&mut self, v: f32) {
         self.buffer[self.index] = v;
 
@@ -51,4 +86,64 @@ impl<const N: usize> Delay for MemoryDelay<N> {
             self.index = 0;
         }
     }
+
+    fn clear(&mut self) {
+        self.buffer = [0.0; N];
+        self.index = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clear() {
+        let mut d = MemoryDelay::<8>::default();
+        d.set_sample_count(4);
+
+        for i in 0..4 {
+            d.write(i as f32 + 1.0);
+        }
+        assert_eq!(d.read(), 1.0);
+
+        d.clear();
+        assert_eq!(d.sample_count(), 4);
+
+        for _ in 0..4 {
+            assert_eq!(d.read(), 0.0);
+            d.write(5.0);
+        }
+        assert_eq!(d.read(), 5.0);
+    }
+
+    #[test]
+    fn read_at() {
+        let mut d = MemoryDelay::<8>::default();
+        d.set_sample_count(4);
+
+        for i in 0..6 {
+            d.write(i as f32);
+        }
+
+        assert_eq!(d.read_at(0), 5.0);
+        assert_eq!(d.read_at(1), 4.0);
+        assert_eq!(d.read_at(3), 2.0);
+        assert_eq!(d.read_at(3), d.read());
+        assert_eq!(d.read_at(4), 5.0);
+    }
+
+    #[test]
+    fn read_frac() {
+        let mut d = MemoryDelay::<8>::default();
+        d.set_sample_count(4);
+
+        for i in 0..4 {
+            d.write(i as f32);
+        }
+
+        assert_eq!(d.read_frac(0.0), 3.0);
+        assert_eq!(d.read_frac(0.5), 2.5);
+        assert_eq!(d.read_frac(1.25), 1.75);
+    }
 }
@@ -16,6 +16,9 @@ struct DiffusionStep<D, const C: usize> {
 
     /// Whether to flip polarity for the channel
     flip_polarity: [bool; C],
+
+    /// Delay in samples of each channel.
+    latency: [usize; C],
 }
 
 impl<D: Delay, const C: usize> DiffusionStep<D, C> {
@@ -31,9 +34,17 @@ impl<D: Delay, const C: usize> DiffusionStep<D, C> {
     ///
     ///      delay_range
     /// ```
+    ///
+    /// Each channel is guaranteed a longer delay than the previous, also when the range
+    /// is too small to fit all buckets. The delay `D` must hold a sample count of
+    /// `delay_range_secs * sample_rate + C`.
     pub fn new(sample_rate: usize, delay_range_secs: f32, rnd: &mut Rnd) -> Self {
         let delay_samples = delay_range_secs * sample_rate as f32;
 
+        // Sample count of the previous channel.
+        let mut prev = 0;
+        let mut latency = [0; C];
+
         let delays: [D; C] = array::from_fn(|i| {
             let lo = (delay_samples * i as f32) / C as f32;
             let hi = (delay_samples * (i as f32 + 1.0)) / C as f32;
@@ -41,10 +52,17 @@ impl<D: Delay, const C: usize> DiffusionStep<D, C> {
             let n = rnd.next();
 
             let range = hi - lo;
-            let delay_size = (range * (n as f32 / u32::MAX as f32)) as usize;
+            let delay_size = (lo + range * (n as f32 / u32::MAX as f32)) as usize;
+
+            // Always more than the previous bucket.
+            let sample_count = (delay_size + 1).max(prev + 1);
+            prev = sample_count;
+
+            // Written before read, which means a sample count of 1 is no delay.
+            latency[i] = sample_count - 1;
 
             let mut d = D::default();
-            d.set_sample_count(delay_size + 1);
+            d.set_sample_count(sample_count);
 
             d
         });
@@ -54,8 +72,14 @@ impl<D: Delay, const C: usize> DiffusionStep<D, C> {
         Self {
             delays,
             flip_polarity,
+            latency,
         }
     }
+
+    /// Delay in samples of the longest channel.
+    fn latency_samples(&self) -> usize {
+        self.latency.iter().copied().max().unwrap_or(0)
+    }
 }
 
 impl<D: Delay, const C: usize> AudioNode<C> for DiffusionStep<D, C> {
@@ -77,6 +101,12 @@ impl<D: Delay, const C: usize> AudioNode<C> for DiffusionStep<D, C> {
 
         mixed
     }
+
+    fn reset(&mut self) {
+        for d in &mut self.delays {
+            d.clear();
+        }
+    }
 }
 
 pub struct Diffuser<D, const C: usize, const S: usize> {
@@ -85,6 +115,10 @@ pub struct Diffuser<D, const C: usize, const S: usize> {
 }
 
 impl<D: Delay, const C: usize, const S: usize> Diffuser<D, C, S> {
+    /// Creates a diffuser of `S` steps, where each step has half the delay range of
+    /// the previous, starting at half of `seconds`.
+    ///
+    /// The delay `D` must hold a sample count of `seconds * sample_rate / 2 + C`.
     pub fn new(sample_rate: usize, mut seconds: f32, rnd: &mut Rnd) -> Self {
         Self {
             steps: array::from_fn(|_| {
@@ -93,6 +127,17 @@ impl<D: Delay, const C: usize, const S: usize> Diffuser<D, C, S> {
             }),
         }
     }
+
+    /// Creates a diffuser with its own random generator from `seed`. The same seed
+    /// always gives the same diffusion network.
+    pub fn from_seed(sample_rate: usize, seconds: f32, seed: u32) -> Self {
+        Self::new(sample_rate, seconds, &mut Rnd::new(seed))
+    }
+
+    /// Delay in samples through all steps, taking the longest channel of each step.
+    pub fn latency_samples(&self) -> usize {
+        self.steps.iter().map(|s| s.latency_samples()).sum()
+    }
 }
 
 impl<D: Delay, const C: usize, const S: usize> AudioNode<C> for Diffuser<D, C, S> {
@@ -102,4 +147,78 @@ impl<D: Delay, const C: usize, const S: usize> AudioNode<C> for Diffuser<D, C, S
         }
         input
     }
+
+    fn reset(&mut self) {
+        for step in &mut self.steps {
+            step.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::audio::MemoryDelay;
+
+    #[test]
+    fn from_seed() {
+        let mut d1 = Diffuser::<MemoryDelay<64>, 4, 2>::from_seed(1000, 0.05, 7);
+        let mut d2 = Diffuser::<MemoryDelay<64>, 4, 2>::from_seed(1000, 0.05, 7);
+
+        for n in 0..100 {
+            let input = if n == 0 {
+                [1.0, 0.5, -0.5, -1.0]
+            } else {
+                [0.0; 4]
+            };
+            assert_eq!(d1.process(input), d2.process(input));
+        }
+    }
+
+    #[test]
+    fn latency_samples() {
+        let mut d = Diffuser::<MemoryDelay<64>, 4, 1>::from_seed(1000, 0.05, 7);
+        let latency = d.latency_samples();
+
+        // With distinct delays, each output sample has at most one channel's impulse
+        // in it, which means the last non-zero output is the longest channel.
+        let last = (0..64)
+            .filter(|n| {
+                let input = if *n == 0 { [1.0; 4] } else { [0.0; 4] };
+                d.process(input) != [0.0; 4]
+            })
+            .last();
+
+        assert_eq!(last, Some(latency));
+        assert!(latency > 0);
+    }
+
+    #[test]
+    fn distinct_delays() {
+        let mut rnd = Rnd::new(1);
+
+        // A range of a single sample.
+        let step = DiffusionStep::<MemoryDelay<16>, 4>::new(1000, 0.001, &mut rnd);
+
+        let counts = step.delays.each_ref().map(|d| d.sample_count());
+
+        for i in 1..4 {
+            assert!(counts[i] > counts[i - 1], "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn bucket_delays() {
+        let mut rnd = Rnd::new(1);
+
+        // Buckets of 10 samples each.
+        let step = DiffusionStep::<MemoryDelay<64>, 4>::new(1000, 0.04, &mut rnd);
+
+        let counts = step.delays.each_ref().map(|d| d.sample_count());
+
+        for (i, c) in counts.iter().enumerate() {
+            assert!((i * 10 + 1..=(i + 1) * 10 + 1).contains(c), "{:?}", counts);
+        }
+    }
 }
@@ -3,6 +3,7 @@ use core::array;
 use micromath::F32Ext;
 
 use crate::audio::householder::transform_householder;
+use crate::geom::sin;
 
 use super::delay::Delay;
 use super::AudioNode;
@@ -13,8 +14,23 @@ pub struct MixedFeedback<D, const C: usize> {
     /// Each delay is sized to max hold sample_rate * max_time_in_sec.
     delays: [D; C],
 
+    /// Sample count of each delay.
+    sizes: [usize; C],
+
     /// The amount of gain decay for each feedback.
     decay: f32,
+
+    /// Sample rate, used for the modulation.
+    sample_rate: usize,
+
+    /// Max modulation of read position in samples. 0.0 is no modulation.
+    mod_depth: f32,
+
+    /// Modulation phase per channel in [`crate::geom::sin`] angles.
+    mod_phase: [u32; C],
+
+    /// Modulation phase increment per sample.
+    mod_step: u32,
 }
 
 impl<D: Delay, const C: usize> MixedFeedback<D, C> {
@@ -22,20 +38,69 @@ impl<D: Delay, const C: usize> MixedFeedback<D, C> {
         let sample_count = sample_rate as f32 * delay_secs;
 
         let mut delays: [D; C] = array::from_fn(|_| D::default());
+        let mut sizes = [0; C];
 
         for i in 0..C {
             let r = i as f32 / C as f32;
             let delay_size = (2.0.powf(r) * sample_count) as usize;
             delays[i].set_sample_count(delay_size + 1);
+            sizes[i] = delay_size + 1;
+        }
+
+        Self {
+            delays,
+            sizes,
+            decay,
+            sample_rate,
+            mod_depth: 0.0,
+            mod_phase: initial_phase(),
+            mod_step: 0,
+        }
+    }
+
+    /// Modulate the delay read positions with a slow sine to smear out resonances.
+    ///
+    /// `depth_secs` is the max change of delay time, and `rate_hz` the speed of the
+    /// modulation. A depth of 0.0 turns modulation off.
+    pub fn set_modulation(&mut self, depth_secs: f32, rate_hz: f32) {
+        self.mod_depth = depth_secs * self.sample_rate as f32;
+        self.mod_step = ((rate_hz / self.sample_rate as f32) * u32::MAX as f32) as u32;
+    }
+
+    /// Delay in samples of the longest channel, before the first feedback.
+    pub fn latency_samples(&self) -> usize {
+        // Read before written, which means the delay is the full sample count.
+        self.sizes.iter().copied().max().unwrap_or(0)
+    }
+
+    fn read(&self, i: usize) -> f32 {
+        if self.mod_depth <= 0.0 {
+            return self.delays[i].read();
         }
 
-        Self { delays, decay }
+        // The oldest sample is the unmodulated read. Modulate towards newer samples.
+        let oldest = (self.sizes[i] - 1) as f32;
+
+        // 0.0..1.0
+        let m = (sin(self.mod_phase[i]) as f32 / 32768.0 + 1.0) * 0.5;
+        let depth = self.mod_depth.min(oldest);
+
+        self.delays[i].read_frac(oldest - m * depth)
     }
 }
 
+/// Spread the modulation phases evenly over the channels.
+fn initial_phase<const C: usize>() -> [u32; C] {
+    array::from_fn(|i| ((u32::MAX as u64 * i as u64) / C as u64) as u32)
+}
+
 impl<D: Delay, const C: usize> AudioNode<C> for MixedFeedback<D, C> {
     fn process(&mut self, input: [f32; C]) -> [f32; C] {
-        let mut delayed: [_; C] = array::from_fn(|i| self.delays[i].read());
+        let mut delayed: [_; C] = array::from_fn(|i| self.read(i));
+
+        for phase in &mut self.mod_phase {
+            *phase = phase.wrapping_add(self.mod_step);
+        }
 
         // Mix a bit of all channels into all channels.
         let mixed = delayed.clone();
@@ -49,4 +114,49 @@ impl<D: Delay, const C: usize> AudioNode<C> for MixedFeedback<D, C> {
 
         mixed
     }
+
+    fn reset(&mut self) {
+        for d in &mut self.delays {
+            d.clear();
+        }
+        self.mod_phase = initial_phase();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::audio::MemoryDelay;
+
+    fn run(depth_secs: f32) -> Vec<f32> {
+        let mut f = MixedFeedback::<MemoryDelay<128>, 4>::new(1000, 0.05, 0.5);
+        f.set_modulation(depth_secs, 2.0);
+
+        // Sustained tone with a period of 20 samples.
+        (0..2000)
+            .map(|n| {
+                let v = (n as f32 * core::f32::consts::PI / 10.0).sin();
+                f.process([v; 4])[0]
+            })
+            .collect()
+    }
+
+    /// Max difference between samples one period of the input apart.
+    fn max_period_diff(out: &[f32]) -> f32 {
+        out[1500..]
+            .iter()
+            .zip(&out[1480..])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn modulation() {
+        // Without modulation, the output settles to follow the input.
+        assert!(max_period_diff(&run(0.0)) < 0.001);
+
+        // With modulation the output keeps changing.
+        assert!(max_period_diff(&run(0.005)) > 0.01);
+    }
 }
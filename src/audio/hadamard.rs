@@ -27,10 +27,47 @@ use micromath::F32Ext;
 ///
 /// The input must be a power of 2.
 pub fn transform_hadamard<const C: usize>(samples: &mut [f32; C]) {
+    butterfly(samples);
+
+    const fn factor<const C: usize>() -> f32 {
+        // Soft32 here is while we wait for this to land:
+        // https://github.com/rust-lang/rust/issues/57241
+        (SoftF32(1.0).div(SoftF32(C as f32))).sqrt().to_f32()
+    }
+
+    // Scale down to orthogonal
+    for b in samples {
+        *b *= factor::<C>();
+    }
+}
+
+/// Runtime sized version of [`transform_hadamard`].
+///
+/// The length must be a power of 2, otherwise the samples are left untouched.
+pub fn transform_hadamard_slice(samples: &mut [f32]) {
+    let len = samples.len();
+
+    if !len.is_power_of_two() {
+        return;
+    }
+
+    butterfly(samples);
+
+    let factor = (1.0 / len as f32).sqrt();
+
+    // Scale down to orthogonal
+    for b in samples {
+        *b *= factor;
+    }
+}
+
+/// The unscaled hadamard mixing.
+fn butterfly(samples: &mut [f32]) {
+    let len = samples.len();
     let mut stride = 1;
 
-    while stride < C {
-        for i in (0..C).step_by(stride * 2) {
+    while stride < len {
+        for i in (0..len).step_by(stride * 2) {
             for j in 0..stride {
                 let a = samples[i + j];
                 let b = samples[i + j + stride];
@@ -43,17 +80,6 @@ pub fn transform_hadamard<const C: usize>(samples: &mut [f32; C]) {
         }
         stride *= 2;
     }
-
-    const fn factor<const C: usize>() -> f32 {
-        // Soft32 here is while we wait for this to land:
-        // https://github.com/rust-lang/rust/issues/57241
-        (SoftF32(1.0).div(SoftF32(C as f32))).sqrt().to_f32()
-    }
-
-    // Scale down to orthogonal
-    for b in samples {
-        *b *= factor::<C>();
-    }
 }
 
 #[cfg(test)]
@@ -72,4 +98,19 @@ mod test {
         transform_hadamard(&mut input);
         assert_eq!(input, [5.0, -1.0, -2.0, 0.0].map(F32Cmp));
     }
+
+    #[test]
+    fn test_transform_hadamard_slice() {
+        let mut input = [1., 2., 3., 4.];
+        let mut slice = input;
+
+        transform_hadamard(&mut input);
+        transform_hadamard_slice(&mut slice[..]);
+        assert_eq!(slice, input.map(F32Cmp));
+
+        // Not a power of 2 is left as is.
+        let mut slice = [1., 2., 3.];
+        transform_hadamard_slice(&mut slice[..]);
+        assert_eq!(slice, [1., 2., 3.]);
+    }
 }
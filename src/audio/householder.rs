@@ -17,6 +17,19 @@ pub fn transform_householder<const C: usize>(samples: &mut [f32; C]) {
     }
 }
 
+/// Runtime sized version of [`transform_householder`].
+pub fn transform_householder_slice(samples: &mut [f32]) {
+    if samples.is_empty() {
+        return;
+    }
+
+    let k = samples.iter().sum::<f32>() * (-2.0 / samples.len() as f32);
+
+    for b in samples {
+        *b += k;
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -33,4 +46,16 @@ mod test {
         transform_householder(&mut input);
         assert_eq!(input, [2.0, -1.0, 4.0, -3.0].map(F32Cmp));
     }
+
+    #[test]
+    fn test_transform_householder_slice() {
+        use crate::f32cmp::F32Cmp;
+
+        let mut input = [1.0, -2.0, 3.0, -4.0];
+        let mut slice = input;
+
+        transform_householder(&mut input);
+        transform_householder_slice(&mut slice[..]);
+        assert_eq!(slice, input.map(F32Cmp));
+    }
 }
@@ -12,9 +12,17 @@ mod feedback;
 mod hadamard;
 mod householder;
 mod reverb;
+mod saturate;
 
+pub use delay::{Delay, MemoryDelay};
+pub use hadamard::{transform_hadamard, transform_hadamard_slice};
+pub use householder::{transform_householder, transform_householder_slice};
 pub use reverb::BasicReverb;
+pub use saturate::Saturate;
 
 pub trait AudioNode<const C: usize> {
     fn process(&mut self, input: [f32; C]) -> [f32; C];
+
+    /// Clear all internal state, such as delay lines, as if the node was just created.
+    fn reset(&mut self) {}
 }
@@ -1,8 +1,6 @@
 #[allow(unused_imports)]
 use micromath::F32Ext;
 
-use crate::rnd::Rnd;
-
 use super::delay::Delay;
 use super::diffusion::Diffuser;
 use super::feedback::MixedFeedback;
@@ -13,13 +11,25 @@ pub struct BasicReverb<D, const C: usize, const S: usize> {
     feedback: MixedFeedback<D, C>,
     dry: f32,
     wet: f32,
+    early_level: f32,
+    late_level: f32,
 }
 
 impl<D: Delay, const C: usize, const S: usize> BasicReverb<D, C, S> {
     pub fn new(sample_rate: usize, room_size_secs: f32, rt60: f32, dry: f32, wet: f32) -> Self {
-        let mut rnd = Rnd::new(82734);
+        Self::with_seed(sample_rate, room_size_secs, rt60, dry, wet, 82734)
+    }
 
-        let diffuser = Diffuser::new(sample_rate, room_size_secs, &mut rnd);
+    /// Creates a reverb where the diffusion network is randomized from `seed`.
+    pub fn with_seed(
+        sample_rate: usize,
+        room_size_secs: f32,
+        rt60: f32,
+        dry: f32,
+        wet: f32,
+        seed: u32,
+    ) -> Self {
+        let diffuser = Diffuser::from_seed(sample_rate, room_size_secs, seed);
 
         // How long does our signal take to go around the feedback loop?
         let typical_loop_secs = room_size_secs * 1.5;
@@ -35,21 +45,185 @@ impl<D: Delay, const C: usize, const S: usize> BasicReverb<D, C, S> {
         Self {
             dry,
             wet,
+            early_level: 0.0,
+            late_level: 1.0,
             diffuser,
             feedback,
         }
     }
+
+    /// Level of the unprocessed input in the output.
+    pub fn dry(&self) -> f32 {
+        self.dry
+    }
+
+    /// Set the level of the unprocessed input in the output.
+    pub fn set_dry(&mut self, dry: f32) {
+        self.dry = dry;
+    }
+
+    /// Level of the reverb in the output.
+    pub fn wet(&self) -> f32 {
+        self.wet
+    }
+
+    /// Set the level of the reverb in the output.
+    pub fn set_wet(&mut self, wet: f32) {
+        self.wet = wet;
+    }
+
+    /// Level of the early reflections in the wet signal. Defaults to 0.0.
+    pub fn early_level(&self) -> f32 {
+        self.early_level
+    }
+
+    /// Set the level of the early reflections in the wet signal.
+    pub fn set_early_level(&mut self, early_level: f32) {
+        self.early_level = early_level;
+    }
+
+    /// Level of the late tail in the wet signal. Defaults to 1.0.
+    pub fn late_level(&self) -> f32 {
+        self.late_level
+    }
+
+    /// Set the level of the late tail in the wet signal.
+    pub fn set_late_level(&mut self, late_level: f32) {
+        self.late_level = late_level;
+    }
+
+    /// Modulate the feedback delay lines with a slow sine for a smoother tail.
+    ///
+    /// `depth_secs` is the max change of delay time, and `rate_hz` the speed of the
+    /// modulation. A depth of 0.0 (the default) is no modulation.
+    pub fn set_modulation(&mut self, depth_secs: f32, rate_hz: f32) {
+        self.feedback.set_modulation(depth_secs, rate_hz);
+    }
+
+    /// Delay in samples of the longest path from the input through the diffuser and the
+    /// feedback, i.e. the last arrival of the first pass through the late tail.
+    pub fn latency_samples(&self) -> usize {
+        self.diffuser.latency_samples() + self.feedback.latency_samples()
+    }
+
+    /// Process the input, but get the early reflections and late tail separately.
+    ///
+    /// Returns `(early, late)` where `early` is the output of the diffuser, and `late`
+    /// is the output of the feedback loop. Neither is scaled with any level, which
+    /// means `process()` is the same as
+    /// `input * dry + (early * early_level + late * late_level) * wet`.
+    pub fn process_split(&mut self, input: [f32; C]) -> ([f32; C], [f32; C]) {
+        let early = self.diffuser.process(input);
+        let late = self.feedback.process(early);
+
+        (early, late)
+    }
 }
 
 impl<D: Delay, const C: usize, const S: usize> AudioNode<C> for BasicReverb<D, C, S> {
     fn process(&mut self, input: [f32; C]) -> [f32; C] {
-        let diffuse = self.diffuser.process(input);
-        let mut mixed = self.feedback.process(diffuse);
+        let (early, late) = self.process_split(input);
 
-        for i in 0..C {
-            mixed[i] = input[i] * self.dry + mixed[i] * self.wet;
+        core::array::from_fn(|i| {
+            let wet = early[i] * self.early_level + late[i] * self.late_level;
+            input[i] * self.dry + wet * self.wet
+        })
+    }
+
+    fn reset(&mut self) {
+        self.diffuser.reset();
+        self.feedback.reset();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::audio::MemoryDelay;
+    use crate::f32cmp::F32Cmp;
+
+    type Reverb = BasicReverb<MemoryDelay<256>, 4, 2>;
+
+    #[test]
+    fn process_split() {
+        let mut r1 = Reverb::new(1000, 0.05, 0.5, 0.6, 0.4);
+        let mut r2 = Reverb::new(1000, 0.05, 0.5, 0.6, 0.4);
+
+        for n in 0..300 {
+            let input = if n == 0 {
+                [1.0, 0.5, -0.5, -1.0]
+            } else {
+                [0.0; 4]
+            };
+
+            let expected = r1.process(input);
+            let (_, late) = r2.process_split(input);
+
+            let sum: [f32; 4] = core::array::from_fn(|i| input[i] * 0.6 + late[i] * 0.4);
+
+            assert_eq!(sum, expected.map(F32Cmp));
+        }
+    }
+
+    #[test]
+    fn reset() {
+        let mut r = Reverb::new(1000, 0.05, 0.5, 0.6, 0.4);
+
+        r.process([1.0, 0.5, -0.5, -1.0]);
+        for _ in 0..10 {
+            r.process([0.0; 4]);
+        }
+
+        r.reset();
+
+        for _ in 0..300 {
+            assert_eq!(r.process([0.0; 4]), [0.0; 4]);
+        }
+    }
+
+    #[test]
+    fn early_late_level() {
+        let mut r1 = Reverb::new(1000, 0.05, 0.5, 0.6, 0.4);
+        let mut r2 = Reverb::new(1000, 0.05, 0.5, 0.6, 0.4);
+
+        r1.set_early_level(1.0);
+        r1.set_late_level(0.0);
+
+        for n in 0..300 {
+            let input = if n == 0 {
+                [1.0, 0.5, -0.5, -1.0]
+            } else {
+                [0.0; 4]
+            };
+
+            let out = r1.process(input);
+            let (early, _) = r2.process_split(input);
+
+            let expected: [f32; 4] = core::array::from_fn(|i| input[i] * 0.6 + early[i] * 0.4);
+
+            assert_eq!(out, expected.map(F32Cmp));
         }
+    }
+
+    #[test]
+    fn set_wet_dry() {
+        let mut r = Reverb::new(1000, 0.05, 0.5, 0.6, 0.4);
 
-        mixed
+        r.set_wet(0.0);
+        r.set_dry(0.8);
+        assert_eq!(r.wet(), 0.0);
+        assert_eq!(r.dry(), 0.8);
+
+        for n in 0..300 {
+            let input = if n % 50 == 0 {
+                [1.0, 0.5, -0.5, -1.0]
+            } else {
+                [0.0; 4]
+            };
+            let expected = input.map(|v| v * 0.8);
+
+            assert_eq!(r.process(input), expected.map(F32Cmp));
+        }
     }
 }
@@ -0,0 +1,73 @@
+use super::AudioNode;
+
+/// Soft clipper that smoothly compresses peaks towards ±1.0.
+///
+/// Uses a rational approximation of `tanh`, which reaches exactly ±1.0 at ±3.0 and
+/// is clamped beyond that.
+pub struct Saturate<const C: usize> {
+    /// Gain before the clipper. Higher values saturate more.
+    drive: f32,
+}
+
+impl<const C: usize> Saturate<C> {
+    pub fn new(drive: f32) -> Self {
+        Saturate { drive }
+    }
+
+    /// Gain before the clipper.
+    pub fn drive(&self) -> f32 {
+        self.drive
+    }
+
+    /// Set the gain before the clipper. Higher values saturate more.
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive;
+    }
+}
+
+/// Padé approximation of `tanh`.
+fn soft_clip(x: f32) -> f32 {
+    if x >= 3.0 {
+        1.0
+    } else if x <= -3.0 {
+        -1.0
+    } else {
+        let x2 = x * x;
+        x * (27.0 + x2) / (27.0 + 9.0 * x2)
+    }
+}
+
+impl<const C: usize> AudioNode<C> for Saturate<C> {
+    fn process(&mut self, input: [f32; C]) -> [f32; C] {
+        input.map(|v| soft_clip(v * self.drive))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saturate() {
+        let mut s = Saturate::<2>::new(1.0);
+
+        for i in -50..=50 {
+            let v = i as f32 / 100.0;
+            let [a, b] = s.process([v, -v]);
+            assert!((a - v).abs() < 0.05, "{} {}", v, a);
+            assert!((b + v).abs() < 0.05, "{} {}", v, b);
+        }
+
+        let [a, b] = s.process([2.0, -100.0]);
+        assert!(a > 0.9 && a <= 1.0, "{}", a);
+        assert_eq!(b, -1.0);
+
+        // Monotonic towards the limit.
+        let mut prev = 0.0;
+        for i in 1..100 {
+            let [v, _] = s.process([i as f32 * 0.05, 0.0]);
+            assert!(v >= prev && v <= 1.0);
+            prev = v;
+        }
+    }
+}
@@ -1,5 +1,9 @@
 //! CPU cycle based clock.
 
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
 use gcd::Gcd;
 
 /// Clock based on cpu cycles. This clock provides 64 bits of time using a sample function that
@@ -16,20 +20,42 @@ pub struct Clock<S, const FQ: u32> {
     half_way: u32,
     upper: u32,
     lower: u32,
+    /// Adjustment of the counted cycles in parts-per-million.
+    skew: i32,
+    /// Raw cycle count when the skew was last changed.
+    skew_anchor_count: i64,
+    /// Adjusted time when the skew was last changed.
+    skew_anchor_time: i64,
+    /// Number of ticks that came too late to be sure of the time. Debug builds only.
+    missed_ticks: u32,
 }
 
 impl<S, const FQ: u32> defmt::Format for Clock<S, FQ> {
     fn format(&self, fmt: defmt::Formatter) {
         defmt::write!(
             fmt,
-            "Clock {{ bits: {}, upper: {}, lower: {} }}",
+            "Clock {{ bits: {}, upper: {}, lower: {}, skew: {} }}",
             self.bits,
             self.upper,
-            self.lower
+            self.lower,
+            self.skew
         )
     }
 }
 
+impl<S, const FQ: u32> Clock<S, FQ> {
+    /// The max time between calls to `tick()` to safely track the 32 bit cycle count of
+    /// `new()`. This is half the time of the sample_fn looping around.
+    ///
+    /// This doesn't apply to `new_with_bits()` with fewer bits, where the max interval
+    /// is shorter, `2.pow(bits - 1)` cycles.
+    ///
+    /// Example: If the CPU speed is 600Mhz, this is 2.pow(31) / 600E6 ≈ 3.58 seconds.
+    pub const fn max_tick_interval() -> Time<FQ> {
+        Time::new(1 << 31)
+    }
+}
+
 impl<S, const FQ: u32> Clock<S, FQ>
 where
     S: Fn() -> u32,
@@ -50,14 +76,49 @@ where
             half_way: 2_u32.pow(bits - 1),
             upper: 0,
             lower: start,
+            skew: 0,
+            skew_anchor_count: 0,
+            skew_anchor_time: 0,
+            missed_ticks: 0,
         }
     }
 
+    /// Number of calls to `tick()` that were more than half the range of the sample_fn
+    /// apart, which means the time might be wrong. For the 32 bits of `new()`, that is
+    /// `max_tick_interval()`. This is only counted in debug builds, and is
+    /// always 0 in release builds.
+    pub fn missed_ticks(&self) -> u32 {
+        self.missed_ticks
+    }
+
+    /// Trim the effective frequency of the clock in parts-per-million. The crystal never
+    /// runs at exactly `FQ`, and a positive skew makes `now()` count faster to compensate
+    /// for a crystal running slow. The default is 0.
+    ///
+    /// The skew applies to cycles counted from the last `tick()` onwards, which means the
+    /// time doesn't jump when the skew is changed.
+    pub fn set_skew_ppm(&mut self, ppm: i32) {
+        self.skew_anchor_time = self.now().count;
+        self.skew_anchor_count = self.count();
+        self.skew = ppm;
+    }
+
     /// Sample the current CPU clock and update the internal clock state. This must be done often
     /// enough that `sample_fn` doesn't risk looping twice.
     pub fn tick(&mut self) {
         let cur = (self.sample_fn)();
 
+        #[cfg(debug_assertions)]
+        {
+            // Cycles since last sample, within the bits of the sample_fn.
+            let mask = u32::MAX >> (32 - self.bits);
+            let delta = cur.wrapping_sub(self.lower) & mask;
+
+            if delta >= self.half_way {
+                self.missed_ticks = self.missed_ticks.saturating_add(1);
+            }
+        }
+
         if cur < self.half_way && self.lower > self.half_way {
             // we have looped around.
             self.upper += 1;
@@ -68,11 +129,28 @@ where
 
     /// Get the current time. This is reasonably called _after_ `tick()`.
     pub fn now(&self) -> Time<FQ> {
+        // Cycles since the skew was last changed.
+        let elapsed = self.count() - self.skew_anchor_count;
+
+        let adjust = (elapsed as i128 * self.skew as i128) / 1_000_000;
+
         Time {
-            count: ((self.upper as i64) << self.bits) | (self.lower as i64),
+            count: self.skew_anchor_time + elapsed + adjust as i64,
         }
     }
 
+    /// Cycles counted since the clock was created, without any skew.
+    fn count(&self) -> i64 {
+        ((self.upper as i64) << self.bits) | (self.lower as i64)
+    }
+
+    /// Sample the current CPU clock and get the current time. This is `tick()` followed
+    /// by `now()`, for callers that only need an occasional timestamp.
+    pub fn now_sampled(&mut self) -> Time<FQ> {
+        self.tick();
+        self.now()
+    }
+
     pub fn delay_nanos(&mut self, ns: u64) {
         self.tick();
         let start = self.now();
@@ -86,6 +164,83 @@ where
     }
 }
 
+/// Create a future that resolves once `duration` has passed according to the `clock`.
+///
+/// The duration is counted from the first time the future is polled.
+pub fn timer<S, const FQ: u32>(clock: &mut Clock<S, FQ>, duration: Time<FQ>) -> Timer<'_, S, FQ>
+where
+    S: Fn() -> u32,
+{
+    Timer {
+        clock,
+        duration,
+        deadline: None,
+    }
+}
+
+/// Future that is ready once a duration has passed. See [`timer`].
+///
+/// The clock is ticked every poll.
+pub struct Timer<'a, S, const FQ: u32> {
+    clock: &'a mut Clock<S, FQ>,
+    duration: Time<FQ>,
+    deadline: Option<Time<FQ>>,
+}
+
+impl<'a, S, const FQ: u32> Future for Timer<'a, S, FQ>
+where
+    S: Fn() -> u32,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        this.clock.tick();
+        let now = this.clock.now();
+
+        let deadline = *this.deadline.get_or_insert(now + this.duration);
+
+        if now >= deadline {
+            Poll::Ready(())
+        } else {
+            // The executor polls continuously, no need to wake.
+            Poll::Pending
+        }
+    }
+}
+
+/// Clock divider that lets through every `N`th tick.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Divider<const N: u32> {
+    /// Ticks since the last emitted.
+    count: u32,
+}
+
+impl<const N: u32> Divider<N> {
+    pub const fn new() -> Self {
+        Divider { count: 0 }
+    }
+
+    /// Feed an input tick at `now`. Returns `now` for every `N`th tick, counted
+    /// from construction or the last `reset()`.
+    pub fn tick<const FQ: u32>(&mut self, now: Time<FQ>) -> Option<Time<FQ>> {
+        self.count += 1;
+
+        if self.count >= N {
+            self.count = 0;
+            Some(now)
+        } else {
+            None
+        }
+    }
+
+    /// Start over, which means the next output is after another `N` ticks.
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
 /// A time representation as produced by `Clock::now()`.
 #[derive(Clone, Copy, PartialEq, Eq, Default, defmt::Format)]
 pub struct Time<const FQ: u32> {
@@ -163,6 +318,32 @@ impl<const FQ: u32> Time<FQ> {
         }
     }
 
+    /// Convert this timestamp to another clock domain.
+    ///
+    /// Like `rebase()`, but the intermediate math is done with 128 bits, so it neither
+    /// overflows for large counts nor loses precision for high frequencies.
+    pub const fn convert<const FQ2: u32>(&self) -> Time<FQ2> {
+        Time {
+            count: ((self.count as i128 * FQ2 as i128) / FQ as i128) as i64,
+        }
+    }
+
+    /// The count as little endian bytes, for instance to send over the wire.
+    ///
+    /// The frequency `FQ` is not part of the bytes, which means the receiving end must
+    /// use the same `FQ` in [`Time::from_le_bytes`].
+    pub const fn to_le_bytes(&self) -> [u8; 8] {
+        self.count.to_le_bytes()
+    }
+
+    /// Create a new instance from little endian bytes made by [`Time::to_le_bytes`],
+    /// using the same `FQ`.
+    pub const fn from_le_bytes(bytes: [u8; 8]) -> Self {
+        Time {
+            count: i64::from_le_bytes(bytes),
+        }
+    }
+
     /// Fractional seconds in nanoseconds. I.e. if time is 500E6 and clock frequency is 600E6,
     /// this function returns 833_333_333.
     pub fn subsec_nanos(&self) -> i64 {
@@ -179,6 +360,44 @@ impl<const FQ: u32> Time<FQ> {
     pub fn count(&self) -> i64 {
         self.count
     }
+
+    /// Const version of `<`.
+    pub const fn lt(&self, other: &Self) -> bool {
+        self.count < other.count
+    }
+
+    /// Const version of `<=`.
+    pub const fn le(&self, other: &Self) -> bool {
+        self.count <= other.count
+    }
+
+    /// Const version of `>`.
+    pub const fn gt(&self, other: &Self) -> bool {
+        self.count > other.count
+    }
+
+    /// Const version of `>=`.
+    pub const fn ge(&self, other: &Self) -> bool {
+        self.count >= other.count
+    }
+
+    /// Const version of `Ord::min`.
+    pub const fn min(self, other: Self) -> Self {
+        if self.count <= other.count {
+            self
+        } else {
+            other
+        }
+    }
+
+    /// Const version of `Ord::max`.
+    pub const fn max(self, other: Self) -> Self {
+        if self.count >= other.count {
+            self
+        } else {
+            other
+        }
+    }
 }
 
 impl<const FQ: u32> core::fmt::Display for Time<FQ> {
@@ -295,4 +514,178 @@ mod test {
 
         assert_eq!(t4.count, 0);
     }
+
+    #[test]
+    fn timer_polls() {
+        use crate::exec::executor;
+        use core::cell::Cell;
+
+        let cycles = Cell::new(0);
+        let sample = || {
+            let c = cycles.get();
+            cycles.set(c + 10);
+            c
+        };
+
+        let mut clock = Clock::<_, 1000>::new(sample);
+
+        executor(async { timer(&mut clock, Time::new(50)).await });
+
+        // One sample in Clock::new, then one per poll. The first poll is at 10,
+        // and the deadline at 60.
+        let polls = cycles.get() / 10 - 1;
+        assert_eq!(polls, 6);
+    }
+
+    #[test]
+    fn now_sampled_wraps() {
+        use core::cell::Cell;
+
+        let cycles = Cell::new(0xf000_0000);
+        let mut clock = Clock::<_, 1000>::new(|| cycles.get());
+
+        // Past the half way wrap point.
+        cycles.set(0x1000);
+
+        assert_eq!(clock.now_sampled().count, 0x1_0000_1000);
+    }
+
+    #[test]
+    fn skew_ppm() {
+        use core::cell::Cell;
+
+        let cycles = Cell::new(0);
+        let mut clock = Clock::<_, 1_000_000>::new(|| cycles.get());
+
+        cycles.set(1_000_000);
+        assert_eq!(clock.now_sampled().count, 1_000_000);
+
+        // The skew only applies from when it's set.
+        clock.set_skew_ppm(1000);
+        assert_eq!(clock.now().count, 1_000_000);
+
+        cycles.set(2_000_000);
+        assert_eq!(clock.now_sampled().count, 2_001_000);
+
+        clock.set_skew_ppm(-1000);
+        assert_eq!(clock.now().count, 2_001_000);
+
+        cycles.set(3_000_000);
+        assert_eq!(clock.now_sampled().count, 3_000_000);
+    }
+
+    #[test]
+    fn skew_ppm_monotonic() {
+        use core::cell::Cell;
+
+        let cycles = Cell::new(0);
+        let mut clock = Clock::<_, 1_000_000>::new(|| cycles.get());
+
+        let mut prev = clock.now_sampled();
+
+        for (i, ppm) in [0, 500, -500, 100, -1000, 1000, 0]
+            .into_iter()
+            .cycle()
+            .take(50)
+            .enumerate()
+        {
+            clock.set_skew_ppm(ppm);
+            assert!(clock.now() >= prev);
+
+            cycles.set((i as u32 + 1) * 50_000_000);
+            let now = clock.now_sampled();
+            assert!(now >= prev);
+            prev = now;
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn missed_ticks() {
+        use core::cell::Cell;
+
+        let cycles = Cell::new(0);
+        let mut clock = Clock::<_, 1000>::new(|| cycles.get());
+
+        for c in [0x1000_0000, 0x7000_0000, 0xc000_0000, 0x1000_0000] {
+            cycles.set(c);
+            clock.tick();
+        }
+        assert_eq!(clock.missed_ticks(), 0);
+
+        // More than half the 32 bits since the last tick.
+        cycles.set(0x9000_0001);
+        clock.tick();
+        assert_eq!(clock.missed_ticks(), 1);
+    }
+
+    #[test]
+    fn divider() {
+        let mut d = Divider::<4>::new();
+
+        let emitted: Vec<_> = (1..=12)
+            .filter_map(|i| d.tick(Time::<1000>::new(i)))
+            .map(|t| t.count())
+            .collect();
+        assert_eq!(emitted, [4, 8, 12]);
+
+        // Reset mid way aligns to the reset.
+        d.tick(Time::<1000>::new(13));
+        d.tick(Time::<1000>::new(14));
+        d.reset();
+
+        let emitted: Vec<_> = (15..=22)
+            .filter_map(|i| d.tick(Time::<1000>::new(i)))
+            .map(|t| t.count())
+            .collect();
+        assert_eq!(emitted, [18, 22]);
+    }
+
+    #[test]
+    fn max_tick_interval() {
+        let t = Clock::<fn() -> u32, 600_000_000>::max_tick_interval();
+        assert_eq!(t.seconds(), 3);
+        assert_eq!(t.subsec_millis(), 579);
+    }
+
+    #[test]
+    fn time_convert() {
+        let t = Time::<600_000_000>::from_millis(1500);
+        let c: Time<48_000> = t.convert();
+        assert_eq!(c.count(), 72_000);
+
+        // Overflows with 64 bit intermediates.
+        let t = Time::<600_000_000>::new(i64::MAX / 1000);
+        let c: Time<600_000_000> = t.convert::<48_000>().convert();
+        assert!((t.count() - c.count()) < 600_000_000 / 48_000);
+    }
+
+    #[test]
+    fn time_le_bytes() {
+        let t = Time::<48_000>::new(-12_345);
+        assert_eq!(Time::<48_000>::from_le_bytes(t.to_le_bytes()), t);
+
+        let t = Time::<600_000_000>::new(i64::MAX - 7);
+        assert_eq!(Time::<600_000_000>::from_le_bytes(t.to_le_bytes()), t);
+
+        assert_eq!(Time::<1000>::new(1).to_le_bytes(), [1, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn time_const_cmp() {
+        const A: Time<1000> = Time::from_millis(10);
+        const B: Time<1000> = Time::from_millis(20);
+
+        const MIN: Time<1000> = Time::min(A, B);
+        const MAX: Time<1000> = Time::max(A, B);
+        const ORDERED: [bool; 2] = [A.lt(&B), B.lt(&A)];
+
+        assert_eq!(MIN, A);
+        assert_eq!(MAX, B);
+        assert_eq!(ORDERED, [true, false]);
+        assert!(A.le(&A));
+        assert!(B.gt(&A));
+        assert!(B.ge(&B));
+        assert!(!A.gt(&B));
+    }
 }
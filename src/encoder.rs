@@ -73,18 +73,50 @@ pub struct Encoder<T> {
     prev_next: u8,
     state: u8,
     last_pos: isize,
+    /// Number of detected turns per emitted delta.
+    divisor: u8,
+    /// Turns accumulated towards the divisor.
+    acc: i8,
+    /// Sum of all emitted deltas.
+    position: isize,
+    /// Transitions that didn't follow on from the previous transition.
+    errors: u32,
 }
 
 impl<T> Encoder<T> {
     /// Create an encoder over some quadrature source.
     pub fn new(quad: T) -> Self {
+        Self::with_divisor(quad, 1)
+    }
+
+    /// Create an encoder that only emits a delta every `divisor` turns. This is for
+    /// encoders that have multiple detents per quadrature cycle.
+    pub fn with_divisor(quad: T, divisor: u8) -> Self {
+        assert!(divisor > 0 && divisor <= i8::MAX as u8);
+
         Encoder {
             quad,
             prev_next: 0,
             state: 0,
             last_pos: 4,
+            divisor,
+            acc: 0,
+            position: 0,
+            errors: 0,
         }
     }
+
+    /// The sum of all deltas emitted since construction. CW is positive.
+    pub fn position(&self) -> isize {
+        self.position
+    }
+
+    /// Number of transitions that didn't follow on from the previous, which means
+    /// some reading was missed or garbled. This counts up on noisy or badly wired
+    /// encoders.
+    pub fn error_count(&self) -> u32 {
+        self.errors
+    }
 }
 
 impl<T, const CLK: u32> DeltaInput<CLK> for Encoder<T>
@@ -143,7 +175,25 @@ where
                 self.last_pos = pos;
 
                 if is_new_turn {
-                    return pos.signum() as i8;
+                    let delta = pos.signum() as i8;
+
+                    // Opposite directions cancel out.
+                    self.acc += delta;
+
+                    if self.acc.unsigned_abs() >= self.divisor {
+                        self.acc = 0;
+                        self.position += delta as isize;
+                        return delta;
+                    }
+                }
+            } else {
+                let prev = self.state >> 4;
+
+                // Turning back is not an error, and neither is the very first transition.
+                let reverse = ((self.prev_next & 0b11) << 2) | (self.prev_next >> 2);
+
+                if prev != 0 && prev != reverse {
+                    self.errors = self.errors.saturating_add(1);
                 }
             }
         }
@@ -201,6 +251,8 @@ pub struct EncoderAccelerator<E, const CLK: u32> {
     prev: Reading<CLK>,
     /// Current speed in millionths per milliseconds.
     speed: u32,
+    /// Ceiling for the speed.
+    max_speed: u32,
     /// Accumulator of millionths.
     acc: u32,
     /// When we last emitted a tick value.
@@ -210,6 +262,9 @@ pub struct EncoderAccelerator<E, const CLK: u32> {
 /// Deceleration in millionths per millisecond
 const DECELERATION: u32 = 500;
 
+/// Highest speed possible from readings, in millionths per millisecond.
+const MAX_SPEED: u32 = 1_200_000;
+
 #[derive(Clone, Copy)]
 struct Reading<const CLK: u32>(Time<CLK>, i8);
 
@@ -218,14 +273,26 @@ where
     E: DeltaInput<CLK>,
 {
     pub fn new(encoder: E) -> Self {
+        Self::with_max_speed(encoder, MAX_SPEED)
+    }
+
+    /// Create an accelerator where the speed never goes above `max_speed` millionths
+    /// per millisecond.
+    pub fn with_max_speed(encoder: E, max_speed: u32) -> Self {
         EncoderAccelerator {
             encoder,
             prev: Reading(Time::new(0), 0),
             speed: 0,
+            max_speed,
             acc: 0,
             last_emit: Time::new(0),
         }
     }
+
+    /// Current speed in millionths per millisecond.
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
 }
 
 impl<E, const CLK: u32> DeltaInput<CLK> for EncoderAccelerator<E, CLK>
@@ -261,7 +328,7 @@ where
             };
 
             if speed > self.speed || speed == 0 {
-                self.speed = speed;
+                self.speed = speed.min(self.max_speed);
             }
 
             self.prev = reading;
@@ -298,3 +365,118 @@ where
         0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    /// Quadrature source where the cell is `AB` in the lowest two bits.
+    struct Mock<'a>(&'a Cell<u8>);
+
+    impl<'a> QuadratureSource for Mock<'a> {
+        fn pin_a(&self) -> bool {
+            self.0.get() & 0b10 > 0
+        }
+
+        fn pin_b(&self) -> bool {
+            self.0.get() & 0b01 > 0
+        }
+    }
+
+    const CW: [u8; 4] = [0b01, 0b00, 0b10, 0b11];
+    const CCW: [u8; 4] = [0b10, 0b00, 0b01, 0b11];
+
+    /// Feed a full quadrature cycle and sum the deltas.
+    fn turn<T: QuadratureSource>(e: &mut Encoder<T>, c: &Cell<u8>, states: [u8; 4]) -> i8 {
+        let mut sum = 0;
+        for s in states {
+            c.set(s);
+            sum += DeltaInput::<1000>::tick(e, Time::ZERO);
+        }
+        sum
+    }
+
+    #[test]
+    fn encoder_turns() {
+        let c = Cell::new(0b11);
+        let mut e = Encoder::new(Mock(&c));
+
+        assert_eq!(turn(&mut e, &c, CW), 1);
+        assert_eq!(turn(&mut e, &c, CW), 1);
+        assert_eq!(turn(&mut e, &c, CCW), -1);
+    }
+
+    #[test]
+    fn encoder_divisor() {
+        let c = Cell::new(0b11);
+        let mut e = Encoder::with_divisor(Mock(&c), 2);
+
+        assert_eq!(turn(&mut e, &c, CW), 0);
+        assert_eq!(turn(&mut e, &c, CW), 1);
+        assert_eq!(turn(&mut e, &c, CW), 0);
+        assert_eq!(turn(&mut e, &c, CW), 1);
+
+        // Direction change cancels the accumulated turn.
+        assert_eq!(turn(&mut e, &c, CW), 0);
+        assert_eq!(turn(&mut e, &c, CCW), 0);
+        assert_eq!(turn(&mut e, &c, CCW), 0);
+        assert_eq!(turn(&mut e, &c, CCW), -1);
+    }
+
+    #[test]
+    fn encoder_error_count() {
+        let c = Cell::new(0b11);
+        let mut e = Encoder::new(Mock(&c));
+
+        for _ in 0..3 {
+            turn(&mut e, &c, CW);
+            turn(&mut e, &c, CCW);
+        }
+        assert_eq!(e.error_count(), 0);
+
+        // 01 -> 10 flips both pins, which is an invalid transition.
+        for s in [0b01, 0b10, 0b11] {
+            c.set(s);
+            DeltaInput::<1000>::tick(&mut e, Time::ZERO);
+        }
+        assert_eq!(e.error_count(), 1);
+    }
+
+    #[test]
+    fn encoder_position() {
+        let c = Cell::new(0b11);
+        let mut e = Encoder::new(Mock(&c));
+
+        for _ in 0..3 {
+            turn(&mut e, &c, CW);
+        }
+        assert_eq!(e.position(), 3);
+
+        turn(&mut e, &c, CCW);
+        assert_eq!(e.position(), 2);
+    }
+
+    /// Delta input that moves every tick.
+    struct Spin;
+
+    impl DeltaInput<1000> for Spin {
+        fn tick(&mut self, _now: Time<1000>) -> i8 {
+            1
+        }
+    }
+
+    #[test]
+    fn accelerator_max_speed() {
+        let mut a = EncoderAccelerator::with_max_speed(Spin, 100_000);
+
+        let mut max = 0;
+        for i in 0..20 {
+            a.tick(Time::from_millis(10 + i * 2));
+            assert!(a.speed() <= 100_000);
+            max = max.max(a.speed());
+        }
+
+        assert_eq!(max, 100_000);
+    }
+}
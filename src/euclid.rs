@@ -2,11 +2,43 @@ use crate::pat::{Pattern, PatternGroup};
 
 const EUCLID_MAX: u8 = 64;
 
+/// Reasons [`try_euclid`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum EuclidError {
+    /// Number of steps is 0.
+    NoSteps,
+    /// Length is 0.
+    NoLength,
+    /// Number of steps is more than 64.
+    TooManySteps,
+    /// Length is more than 64.
+    TooLong,
+}
+
+/// Euclidean rhythm distributing `steps` as evenly as possible over `length`.
+///
+/// Panics on invalid input. See [`try_euclid`].
 pub fn euclid(steps: u8, length: u8) -> Pattern {
-    assert!(steps > 0);
-    assert!(length > 0);
-    assert!(steps <= EUCLID_MAX);
-    assert!(length <= EUCLID_MAX);
+    try_euclid(steps, length).unwrap()
+}
+
+/// Euclidean rhythm distributing `steps` as evenly as possible over `length`.
+///
+/// Both `steps` and `length` must be 1..=64. If `length` is shorter than `steps`,
+/// the length is extended to `steps`.
+pub fn try_euclid(steps: u8, length: u8) -> Result<Pattern, EuclidError> {
+    if steps == 0 {
+        return Err(EuclidError::NoSteps);
+    }
+    if length == 0 {
+        return Err(EuclidError::NoLength);
+    }
+    if steps > EUCLID_MAX {
+        return Err(EuclidError::TooManySteps);
+    }
+    if length > EUCLID_MAX {
+        return Err(EuclidError::TooLong);
+    }
 
     // length cannot be shorter than number of steps.
     let length = length.max(steps);
@@ -16,7 +48,7 @@ pub fn euclid(steps: u8, length: u8) -> Pattern {
     let mut l = PatternGroup::new_with("x".into(), steps as usize);
     let mut r = PatternGroup::new_with("-".into(), (length - steps) as usize);
 
-    while r.len() > 0 {
+    while !r.is_empty() {
         let s = l.len().min(r.len());
         let t = l.len().max(r.len());
 
@@ -37,7 +69,34 @@ pub fn euclid(steps: u8, length: u8) -> Pattern {
     let pattern = l.flatten() + r.flatten();
     assert_eq!(pattern.len(), length as usize);
 
-    pattern
+    Ok(pattern)
+}
+
+/// Euclidean rhythm within a euclidean rhythm. The `outer_steps` are distributed over
+/// `length`, where each hit starts a group lasting until the next hit. Each group is
+/// then filled with a euclidean rhythm of `inner_steps`, capped to the group length.
+///
+/// ```ignore
+/// Outer 3 over 8: x--x-x--
+/// Inner 2:        x-xxxx-x
+/// ```
+///
+/// Panics on invalid input, like [`euclid`].
+pub fn euclid_nested(outer_steps: u8, inner_steps: u8, length: u8) -> Pattern {
+    let outer = euclid(outer_steps, length);
+
+    let mut groups = PatternGroup::new();
+    let mut start = 0;
+
+    for i in 1..=outer.len() {
+        if i == outer.len() || outer[i] > 0 {
+            let len = (i - start) as u8;
+            groups.push(euclid(inner_steps.min(len), len));
+            start = i;
+        }
+    }
+
+    groups.flatten()
 }
 
 #[cfg(test)]
@@ -76,6 +135,16 @@ mod test {
         assert_eq!(euclid(15, 16), "|x-xxxxxxxxxxxxxx|");
     }
 
+    #[test]
+    pub fn euclid_nested_stable() {
+        // x--|x-|x-- -> x-x|xx|x-x
+        assert_eq!(euclid_nested(3, 2, 8), "|x-xxxx-x|");
+        // x-----|x----|x---- -> x-x-x-|x-xx-|x-xx-
+        assert_eq!(euclid_nested(3, 3, 16), "|x-x-x-x-xx-x-xx-|");
+        // One step per group is the outer rhythm.
+        assert_eq!(euclid_nested(5, 1, 16), euclid(5, 16));
+    }
+
     // Brute force try all euclid patterns.
     #[test]
     pub fn euclid_all() {
@@ -97,6 +166,15 @@ mod test {
         assert_eq!(euclid(2, 5).offset(6), "|-x--x|");
     }
 
+    #[test]
+    pub fn euclid_errors() {
+        assert_eq!(try_euclid(0, 16), Err(EuclidError::NoSteps));
+        assert_eq!(try_euclid(4, 0), Err(EuclidError::NoLength));
+        assert_eq!(try_euclid(65, 65), Err(EuclidError::TooManySteps));
+        assert_eq!(try_euclid(4, 65), Err(EuclidError::TooLong));
+        assert_eq!(try_euclid(4, 16).unwrap(), "|x---x---x---x---|");
+    }
+
     // use crate::drums::Drums;
 
     // #[test]
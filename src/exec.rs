@@ -5,81 +5,143 @@ use core::task::{Context, Waker};
 use core::{future::Future, task::RawWaker};
 use core::{pin::Pin, task::RawWakerVTable};
 
+/// Number of wakers available to [`executor`].
+const DEFAULT_WAKERS: usize = 8;
+
 /// Run an executor over the "root future" given. Any additional
 /// futures must be added as children using [`zip`] etc.
-pub fn executor<F: Future>(mut future: F) -> F::Output {
+pub fn executor<F: Future>(future: F) -> F::Output {
+    executor_n::<DEFAULT_WAKERS, F>(future)
+}
+
+/// Run an executor over the "root future" with room for `N` distinct wakers.
+///
+/// The root future uses one waker, and each [`zip`] issues one more per child future.
+/// If the wakers run out, the child futures share the waker of their parent.
+pub fn executor_n<const N: usize, F: Future>(future: F) -> F::Output {
+    // Without a budget, the executor never gives up.
+    executor_budget_n::<N, F>(future, None).expect("unbounded executor to complete")
+}
+
+/// Run an executor over the "root future", but give up after `max_polls` polls that
+/// are pending. This is for tests and watchdogs, where a future that never completes
+/// should not hang forever.
+pub fn executor_with_budget<F: Future>(future: F, max_polls: usize) -> Option<F::Output> {
+    executor_budget_n::<DEFAULT_WAKERS, F>(future, Some(max_polls))
+}
+
+/// Run the executor, giving up after `max_polls` pending polls. `None` runs until the
+/// future completes, however long that takes.
+fn executor_budget_n<const N: usize, F: Future>(
+    future: F,
+    max_polls: Option<usize>,
+) -> Option<F::Output> {
     // These tasks are allocated _on the stack_, and mustn't move for the
     // duration of running  this executor to finish. Wakers created from
     // these tasks have pointers to this stack position.
-    let mut tasks = Tasks::new(1); // NB size 1 until we can do allocation
+    let mut tasks = Tasks::<N>::new();
 
-    let waker = tasks.next_raw_waker();
+    let waker = tasks.next_raw_waker().expect("N to be at least 1");
     let waker = unsafe { Waker::from_raw(waker) };
     let mut cx = Context::from_waker(&waker);
 
+    // Future must be dropped before tasks, since it might hold wakers.
+    let mut future = future;
+
+    let mut pending = 0;
+
     loop {
         // Unsafe: We "own" this instance of impl Future, and will not move it
         // while running it to completion.
         match unsafe { Pin::new_unchecked(&mut future) }.poll(&mut cx) {
-            Poll::Pending => continue,
-            Poll::Ready(v) => return v,
+            Poll::Pending => {
+                let Some(max_polls) = max_polls else {
+                    continue;
+                };
+
+                pending += 1;
+                if pending >= max_polls {
+                    return None;
+                }
+            }
+            Poll::Ready(v) => return Some(v),
         }
     }
 }
 
-/// Controls all wakers. Each entry is a waker refcount.
-struct Tasks {
+/// Slab of wakers. Each entry is a waker refcount.
+type Slab = [Option<Wok>];
+
+/// Controls all wakers.
+struct Tasks<const N: usize> {
     /// Waker instances at fixed places in memory. The whole Tasks instance
     /// must be fixed in place to keep the validity of the wakers issued.
-    wakers: [Option<Wok>; 1],
+    wakers: [Option<Wok>; N],
 }
 
-impl Tasks {
-    fn new(_size: usize) -> Self {
-        Tasks { wakers: [None] }
+impl<const N: usize> Tasks<N> {
+    fn new() -> Self {
+        Tasks {
+            wakers: core::array::from_fn(|_| None),
+        }
+    }
+
+    /// Issue a new waker. None if we have run out.
+    fn next_raw_waker(&mut self) -> Option<RawWaker> {
+        // Unsafe: The pointer is to ourselves.
+        unsafe { next_raw_waker(&mut self.wakers as *mut Slab) }
     }
+}
 
-    /// Issue a new waker. Panics if we have run out.
-    fn next_raw_waker(&mut self) -> RawWaker {
-        // if self.wakers.len() == self.wakers.capacity() - 1 {
-        //     panic!("Too many wakers");
-        // }
+/// Issue a new waker in a vacant entry of the slab. None if we have run out.
+///
+/// Unsafe: The slab must not move for the lifetime of the issued waker.
+unsafe fn next_raw_waker(slab: *mut Slab) -> Option<RawWaker> {
+    let wakers = &mut *slab;
 
-        let ptr = self as *mut Tasks;
+    let key = wakers.iter().position(|w| w.is_none())?;
 
-        // let entry = self.wakers.vacant_entry();
-        // let key = entry.key();
-        let w = Wok {
-            ptr,
-            key: 0,
-            count: 1,
-        };
-        self.wakers[0] = Some(w);
-        // entry.insert(w);
+    let w = Wok {
+        slab,
+        key,
+        count: 1,
+    };
+    wakers[key] = Some(w);
 
-        self.wakers[0].as_ref().unwrap().as_raw_waker()
-    }
+    Some(wakers[key].as_ref().unwrap().as_raw_waker())
+}
+
+/// Issue a waker for a child future of the future polled with `parent`.
+///
+/// If `parent` is issued by our executor, the child gets a distinct waker from the
+/// same slab. Otherwise, or if the slab is full, the child shares the `parent` waker.
+fn child_waker(parent: &Waker) -> Waker {
+    if core::ptr::eq(parent.vtable(), &VTABLE) {
+        // Unsafe: The vtable is ours, which means the data is a Wok.
+        let wok = unsafe { &*(parent.data() as *const Wok) };
 
-    fn remove_waker(&mut self, key: usize) {
-        self.wakers[key] = None;
+        if let Some(raw) = unsafe { next_raw_waker(wok.slab) } {
+            return unsafe { Waker::from_raw(raw) };
+        }
     }
+
+    parent.clone()
 }
 
 struct Wok {
-    ptr: *mut Tasks,
+    slab: *mut Slab,
     key: usize,
     count: usize,
 }
 
 impl Wok {
     fn as_raw_waker(&self) -> RawWaker {
-        RawWaker::new(self as *const Wok as *const (), vtable())
+        RawWaker::new(self as *const Wok as *const (), &VTABLE)
     }
 }
 
-fn vtable() -> &'static RawWakerVTable {
-    &RawWakerVTable::new(vt_clone, vt_wake, vt_wake_by_ref, vt_drop)
-}
+/// A static to have a fixed address we can compare to in [`child_waker`].
+static VTABLE: RawWakerVTable = RawWakerVTable::new(vt_clone, vt_wake, vt_wake_by_ref, vt_drop);
 
 /// Unsafe: We expect the Wok pointer to exist for the lifetime of the RawWaker.
 /// This requires the instance of Tasks to not move.
@@ -90,13 +152,15 @@ unsafe fn vt_clone(p: *const ()) -> RawWaker {
 }
 
 /// Unsafe: See vt_clone.
-unsafe fn vt_wake(_p: *const ()) {
-    //
+unsafe fn vt_wake(p: *const ()) {
+    // Waking consumes the waker.
+    vt_wake_by_ref(p);
+    vt_drop(p);
 }
 
 /// Unsafe: See vt_clone.
 unsafe fn vt_wake_by_ref(_p: *const ()) {
-    //
+    // The executor polls continuously, so there is nothing to do.
 }
 
 /// Unsafe: See vt_clone.
@@ -105,8 +169,9 @@ unsafe fn vt_drop(p: *const ()) {
     wok.count -= 1;
 
     if wok.count == 0 {
-        let wakers = &mut (*wok.ptr);
-        wakers.remove_waker(wok.key);
+        let (slab, key) = (wok.slab, wok.key);
+        // This drops the Wok itself.
+        (*slab)[key] = None;
     }
 }
 
@@ -117,11 +182,11 @@ pub fn zip(future1: impl Future + Unpin, future2: impl Future + Unpin) -> impl F
     ZipFuture::new(future1, future2)
 }
 
-struct ZipFuture<F1, F2>(Option<F1>, Option<F2>);
+struct ZipFuture<F1, F2>(Option<F1>, Option<F2>, Option<[Waker; 2]>);
 
 impl<F1: Future + Unpin, F2: Future + Unpin> ZipFuture<F1, F2> {
     fn new(future1: F1, future2: F2) -> Self {
-        ZipFuture(Some(future1), Some(future2))
+        ZipFuture(Some(future1), Some(future2), None)
     }
 }
 
@@ -131,14 +196,21 @@ impl<F1: Future + Unpin, F2: Future + Unpin> Future for ZipFuture<F1, F2> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
+        // Distinct wakers for each contained future.
+        let wakers = this
+            .2
+            .get_or_insert_with(|| [child_waker(cx.waker()), child_waker(cx.waker())]);
+
         if let Some(future1) = this.0.as_mut() {
-            if let Poll::Ready(_) = Pin::new(future1).poll(cx) {
+            let mut cx1 = Context::from_waker(&wakers[0]);
+            if let Poll::Ready(_) = Pin::new(future1).poll(&mut cx1) {
                 this.0.take(); // end polling future 1
             }
         }
 
         if let Some(future2) = this.1.as_mut() {
-            if let Poll::Ready(_) = Pin::new(future2).poll(cx) {
+            let mut cx2 = Context::from_waker(&wakers[1]);
+            if let Poll::Ready(_) = Pin::new(future2).poll(&mut cx2) {
                 this.1.take(); // end polling future 2
             }
         }
@@ -147,7 +219,152 @@ impl<F1: Future + Unpin, F2: Future + Unpin> Future for ZipFuture<F1, F2> {
         if this.0.is_some() || this.1.is_some() {
             Poll::Pending
         } else {
+            // Release the wakers.
+            this.2.take();
+            Poll::Ready(())
+        }
+    }
+}
+
+/// Join two futures together, polling them one after another like [`zip`]. The resulting
+/// future exits when both contained futures are ready, with the output of both.
+pub fn join<F1, F2>(future1: F1, future2: F2) -> impl Future<Output = (F1::Output, F2::Output)>
+where
+    F1: Future + Unpin,
+    F2: Future + Unpin,
+{
+    JoinFuture {
+        future1: Some(future1),
+        future2: Some(future2),
+        output1: None,
+        output2: None,
+        wakers: None,
+    }
+}
+
+struct JoinFuture<F1: Future, F2: Future> {
+    future1: Option<F1>,
+    future2: Option<F2>,
+    output1: Option<F1::Output>,
+    output2: Option<F2::Output>,
+    wakers: Option<[Waker; 2]>,
+}
+
+// The outputs are never pinned.
+impl<F1: Future + Unpin, F2: Future + Unpin> Unpin for JoinFuture<F1, F2> {}
+
+impl<F1: Future + Unpin, F2: Future + Unpin> Future for JoinFuture<F1, F2> {
+    type Output = (F1::Output, F2::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // Distinct wakers for each contained future.
+        let wakers = this
+            .wakers
+            .get_or_insert_with(|| [child_waker(cx.waker()), child_waker(cx.waker())]);
+
+        if let Some(future1) = this.future1.as_mut() {
+            let mut cx1 = Context::from_waker(&wakers[0]);
+            if let Poll::Ready(v) = Pin::new(future1).poll(&mut cx1) {
+                this.output1 = Some(v);
+                this.future1.take(); // end polling future 1
+            }
+        }
+
+        if let Some(future2) = this.future2.as_mut() {
+            let mut cx2 = Context::from_waker(&wakers[1]);
+            if let Poll::Ready(v) = Pin::new(future2).poll(&mut cx2) {
+                this.output2 = Some(v);
+                this.future2.take(); // end polling future 2
+            }
+        }
+
+        // Any still pending?
+        if this.future1.is_some() || this.future2.is_some() {
+            Poll::Pending
+        } else {
+            // Release the wakers.
+            this.wakers.take();
+
+            let output1 = this.output1.take().expect("output1 after ready");
+            let output2 = this.output2.take().expect("output2 after ready");
+
+            Poll::Ready((output1, output2))
+        }
+    }
+}
+
+/// Select the first of two futures to become ready. The first future is always polled
+/// first. The resulting future exits as soon as one contained future is ready, and the
+/// other future is dropped.
+pub fn select<F1, F2>(
+    future1: F1,
+    future2: F2,
+) -> impl Future<Output = Either<F1::Output, F2::Output>>
+where
+    F1: Future + Unpin,
+    F2: Future + Unpin,
+{
+    SelectFuture(future1, future2, None)
+}
+
+/// Output of [`select`]. Left is the first future, and Right the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+struct SelectFuture<F1, F2>(F1, F2, Option<[Waker; 2]>);
+
+impl<F1: Future + Unpin, F2: Future + Unpin> Future for SelectFuture<F1, F2> {
+    type Output = Either<F1::Output, F2::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // Distinct wakers for each contained future.
+        let wakers = this
+            .2
+            .get_or_insert_with(|| [child_waker(cx.waker()), child_waker(cx.waker())]);
+
+        let mut cx1 = Context::from_waker(&wakers[0]);
+        if let Poll::Ready(v) = Pin::new(&mut this.0).poll(&mut cx1) {
+            this.2.take();
+            return Poll::Ready(Either::Left(v));
+        }
+
+        let mut cx2 = Context::from_waker(&wakers[1]);
+        if let Poll::Ready(v) = Pin::new(&mut this.1).poll(&mut cx2) {
+            this.2.take();
+            return Poll::Ready(Either::Right(v));
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Future that is pending for the first `n` polls, and then ready. This is crude
+/// pacing without any time source. See [`crate::clock::timer`] for a clock based one.
+pub fn poll_count(n: usize) -> impl Future<Output = ()> + Unpin {
+    PollCount(n)
+}
+
+struct PollCount(usize);
+
+impl Future for PollCount {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if this.0 == 0 {
             Poll::Ready(())
+        } else {
+            this.0 -= 1;
+            // The executor polls continuously, no need to wake.
+            Poll::Pending
         }
     }
 }
@@ -155,6 +372,8 @@ impl<F1: Future + Unpin, F2: Future + Unpin> Future for ZipFuture<F1, F2> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use core::cell::Cell;
+    use core::pin::pin;
 
     #[test]
     fn test_simple_task() {
@@ -172,4 +391,144 @@ mod test {
             assert_eq!(x, 42);
         }
     }
+
+    /// Future that is pending `n` times. Records the waker it was polled with.
+    struct Pending<'a> {
+        n: usize,
+        waker: &'a Cell<*const ()>,
+    }
+
+    impl<'a> Future for Pending<'a> {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.waker.set(cx.waker().data());
+
+            if self.n == 0 {
+                Poll::Ready(())
+            } else {
+                self.n -= 1;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_zip_three() {
+        let w1 = Cell::new(core::ptr::null());
+        let w2 = Cell::new(core::ptr::null());
+        let w3 = Cell::new(core::ptr::null());
+
+        let f1 = Pending { n: 2, waker: &w1 };
+        let f2 = Pending { n: 5, waker: &w2 };
+        let f3 = Pending { n: 3, waker: &w3 };
+
+        executor(zip(zip(f1, f2), f3));
+
+        // Each future got a distinct waker.
+        assert_ne!(w1.get(), w2.get());
+        assert_ne!(w1.get(), w3.get());
+        assert_ne!(w2.get(), w3.get());
+    }
+
+    #[test]
+    fn test_zip_run_out_of_wakers() {
+        let w1 = Cell::new(core::ptr::null());
+        let w2 = Cell::new(core::ptr::null());
+        let w3 = Cell::new(core::ptr::null());
+
+        let f1 = Pending { n: 2, waker: &w1 };
+        let f2 = Pending { n: 5, waker: &w2 };
+        let f3 = Pending { n: 3, waker: &w3 };
+
+        // Only room for the root waker and the outer zip.
+        executor_n::<3, _>(zip(zip(f1, f2), f3));
+
+        // The inner zip shares the waker.
+        assert_eq!(w1.get(), w2.get());
+        assert_ne!(w1.get(), w3.get());
+    }
+
+    #[test]
+    fn test_join() {
+        let w = Cell::new(core::ptr::null());
+
+        let f1 = pin!(async {
+            Pending { n: 3, waker: &w }.await;
+            42
+        });
+        let f2 = pin!(async { "hello" });
+
+        let (a, b) = executor(join(f1, f2));
+
+        assert_eq!(a, 42);
+        assert_eq!(b, "hello");
+    }
+
+    #[test]
+    fn test_select_left() {
+        let w = Cell::new(core::ptr::null());
+
+        let f1 = pin!(async { 42 });
+        let f2 = Pending { n: 3, waker: &w };
+
+        let x = executor(select(f1, f2));
+
+        assert_eq!(x, Either::Left(42));
+
+        // Right was never polled.
+        assert!(w.get().is_null());
+    }
+
+    #[test]
+    fn test_select_right() {
+        let w = Cell::new(core::ptr::null());
+
+        let f1 = pin!(async {
+            Pending { n: 5, waker: &w }.await;
+            42
+        });
+        let f2 = pin!(async { "hello" });
+
+        let x = executor(select(f1, f2));
+
+        assert_eq!(x, Either::Right("hello"));
+    }
+
+    #[test]
+    fn test_poll_count() {
+        /// Counts the polls of the wrapped future.
+        struct Counted<'a, F>(F, &'a Cell<usize>);
+
+        impl<'a, F: Future + Unpin> Future for Counted<'a, F> {
+            type Output = F::Output;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                self.1.set(self.1.get() + 1);
+                Pin::new(&mut self.0).poll(cx)
+            }
+        }
+
+        let polls = Cell::new(0);
+        executor(Counted(poll_count(5), &polls));
+        assert_eq!(polls.get(), 6);
+
+        let polls = Cell::new(0);
+        executor(Counted(poll_count(0), &polls));
+        assert_eq!(polls.get(), 1);
+    }
+
+    #[test]
+    fn test_executor_with_budget() {
+        let w = Cell::new(core::ptr::null());
+
+        let r = executor_with_budget(Pending { n: 2, waker: &w }, 3);
+        assert_eq!(r, Some(()));
+
+        let r = executor_with_budget(Pending { n: 3, waker: &w }, 3);
+        assert_eq!(r, None);
+
+        let r = executor_with_budget(core::future::pending::<()>(), 10);
+        assert_eq!(r, None);
+    }
 }
@@ -1,30 +1,93 @@
-const TOLERANCE_NEG: f32 = -0.0001;
-const TOLERANCE_POS: f32 = 0.0001;
+/// Default tolerance of [`F32Cmp`] and [`assert_f32_eq!`](crate::assert_f32_eq).
+pub const TOLERANCE: f32 = 0.0001;
 
+/// An f32 that compares equal to values within a tolerance.
 #[derive(Debug, Clone, Copy)]
-pub struct F32Cmp(pub f32);
+pub struct F32Cmp {
+    pub value: f32,
+    pub tolerance: f32,
+}
+
+/// Compare `value` using the default tolerance of ±0.0001.
+#[allow(non_snake_case)]
+pub const fn F32Cmp(value: f32) -> F32Cmp {
+    F32Cmp::with_tolerance(value, TOLERANCE)
+}
+
+impl F32Cmp {
+    /// Compare `value` equal to anything within ±`tolerance`.
+    pub const fn with_tolerance(value: f32, tolerance: f32) -> Self {
+        F32Cmp { value, tolerance }
+    }
+}
 
-fn eq(a: F32Cmp, b: F32Cmp) -> bool {
-    let n = a.0 - b.0;
-    n > TOLERANCE_NEG && n < TOLERANCE_POS
+fn eq(a: f32, b: f32, tolerance: f32) -> bool {
+    let n = a - b;
+    n > -tolerance && n < tolerance
 }
 
 impl PartialEq for F32Cmp {
     fn eq(&self, other: &Self) -> bool {
-        eq(*self, *other)
+        eq(self.value, other.value, self.tolerance.max(other.tolerance))
     }
 }
 
 impl PartialEq<f32> for F32Cmp {
     fn eq(&self, other: &f32) -> bool {
-        eq(*self, F32Cmp(*other))
+        eq(self.value, *other, self.tolerance)
     }
 }
 
 impl PartialEq<F32Cmp> for f32 {
     fn eq(&self, other: &F32Cmp) -> bool {
-        eq(F32Cmp(*self), *other)
+        eq(*self, other.value, other.tolerance)
     }
 }
 
 impl Eq for F32Cmp {}
+
+/// Assert two f32 are equal within a tolerance, by default ±0.0001.
+///
+/// ```
+/// # use alg::assert_f32_eq;
+/// assert_f32_eq!(1.0, 1.00001);
+/// assert_f32_eq!(1.0, 1.001, 0.01);
+/// ```
+#[macro_export]
+macro_rules! assert_f32_eq {
+    ($a:expr, $b:expr) => {
+        $crate::assert_f32_eq!($a, $b, $crate::f32cmp::TOLERANCE)
+    };
+    ($a:expr, $b:expr, $tol:expr) => {{
+        let (a, b, tol): (f32, f32, f32) = ($a, $b, $tol);
+        assert!(
+            $crate::f32cmp::F32Cmp::with_tolerance(a, tol) == b,
+            "assertion `left == right` failed (tolerance {})\n  left: {}\n right: {}",
+            tol,
+            a,
+            b
+        );
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn with_tolerance() {
+        assert_ne!(F32Cmp(1.0), 1.001);
+        assert_eq!(F32Cmp(1.0), 1.00001);
+        assert_eq!(F32Cmp::with_tolerance(1.0, 0.01), 1.001);
+        assert_eq!(F32Cmp::with_tolerance(1.0, 0.01), F32Cmp(1.001));
+
+        assert_f32_eq!(1.0, 1.001, 0.01);
+        assert_f32_eq!(1.0, 1.00001);
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_f32_eq_default() {
+        assert_f32_eq!(1.0, 1.001);
+    }
+}
@@ -5,9 +5,38 @@ use crate::rnd::Rnd;
 const DEFAULT_PATTERN_LEN: u8 = 64;
 const DEFAULT_TRACK_LEN: u8 = 64;
 
+/// Number of root random draws reserved for tracks. The root randomizer is advanced
+/// past the unused tracks, which keeps `Generated::rnd` stable when a track is added.
+const RESERVED_TRACKS: usize = 64;
+
 /// Base for seed since starting at 0 is so boring.
 pub const SEED_BASE: i32 = 0x4144c47;
 
+/// Deterministic seed from a string, such as a name typed by the user. This is
+/// a 32-bit FNV-1a hash over the bytes of the string. Never returns 0.
+pub const fn seed_from_str(s: &str) -> u32 {
+    const FNV_OFFSET: u32 = 0x811c_9dc5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    // No UTF-8 here, we can just use bytes.
+    let bytes = s.as_bytes();
+
+    let mut hash = FNV_OFFSET;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+
+    if hash == 0 {
+        SEED_BASE as u32
+    } else {
+        hash
+    }
+}
+
 pub const STOKAST_PARAMS: Params<4> = Params {
     seed: SEED_BASE as u32,
     pattern_length: 64,
@@ -20,6 +49,8 @@ pub const STOKAST_PARAMS: Params<4> = Params {
             density: 35,
             subdiv: 3,
             rare: &[3, 5],
+            velocity_spread: 0,
+            muted: false,
         },
         TrackParams {
             steps: 0,
@@ -29,6 +60,8 @@ pub const STOKAST_PARAMS: Params<4> = Params {
             density: 30,
             subdiv: 3,
             rare: &[3, 5, 7],
+            velocity_spread: 0,
+            muted: false,
         },
         TrackParams {
             steps: 0,
@@ -38,6 +71,8 @@ pub const STOKAST_PARAMS: Params<4> = Params {
             density: 80,
             subdiv: 4,
             rare: &[],
+            velocity_spread: 0,
+            muted: false,
         },
         TrackParams {
             steps: 0,
@@ -47,6 +82,8 @@ pub const STOKAST_PARAMS: Params<4> = Params {
             density: 50,
             subdiv: 4,
             rare: &[],
+            velocity_spread: 0,
+            muted: false,
         },
     ],
 };
@@ -87,6 +124,11 @@ pub struct TrackParams {
     pub subdiv: u32,
     /// Steps that we don't want much of.
     pub rare: &'static [u8],
+    /// Random variation of velocity for each hit. 0 to keep the default velocity.
+    pub velocity_spread: u8,
+    /// Silence the track. Unlike a length of 0, this leaves the randomness of the
+    /// other tracks untouched, and unmuting gives back the same pattern.
+    pub muted: bool,
 }
 
 impl Default for TrackParams {
@@ -99,15 +141,18 @@ impl Default for TrackParams {
             density: 0,
             subdiv: 0,
             rare: &[],
+            velocity_spread: 0,
+            muted: false,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Generated<const X: usize> {
-    pub pattern_length: u8,
     pub patterns: [Pattern; X],
     pub rnd: Rnd,
+    /// Parameters used to generate the patterns.
+    params: Params<X>,
 }
 
 impl<const X: usize> Default for Generated<X> {
@@ -120,75 +165,170 @@ impl<const X: usize> Default for Generated<X> {
 
 impl<const X: usize> Generated<X> {
     pub fn new(params: Params<X>) -> Self {
-        assert!(params.pattern_length > 0);
+        let mut patterns: [Pattern; X] = [Pattern::default(); X];
 
-        // Root randomizer.
-        let mut rnd = Rnd::new(params.seed);
+        let rnd = generate_patterns(&params, &mut patterns);
 
-        let mut patterns: [Pattern; X] = [Pattern::default(); X];
+        Generated {
+            patterns,
+            rnd,
+            params,
+        }
+    }
 
-        for i in 0..X {
-            let mut seed = rnd.next() + (i as u32);
-            let mut redo_sanity_check = 0;
+    /// Regenerate all patterns in place using a new seed, keeping the other parameters.
+    pub fn reseed(&mut self, seed: u32) {
+        let params = Params {
+            seed,
+            ..self.params
+        };
+        self.regenerate(params);
+    }
 
-            'redo: loop {
-                redo_sanity_check += 1;
-                if redo_sanity_check >= 50 {
-                    panic!("generate: redo loop runaway");
-                }
+    /// Regenerate all patterns in place using new parameters.
+    pub fn regenerate(&mut self, params: Params<X>) {
+        self.rnd = generate_patterns(&params, &mut self.patterns);
+        self.params = params;
+    }
 
-                // generate pattern for this index.
-                debug!(
-                    "Generate track: {} seed: {} params: {:?}",
-                    i, seed, &params.tracks[i]
-                );
-                patterns[i] = generate(
-                    seed,
-                    &params.tracks[i],
-                    params.pattern_length as usize,
-                    true,
-                    true,
-                );
-
-                // if we are not randomizing, whatever we got is fine.
-                if params.tracks[i].steps > 0 {
-                    break;
-                }
+    pub fn len(&self) -> usize {
+        X
+    }
 
-                // since we randomized, ensure we didn't end up with something that is exactly
-                // like what we already have.
-                let p = &patterns[i];
+    /// Length of the entire pattern, from the parameters.
+    pub fn pattern_length(&self) -> u8 {
+        self.params.pattern_length
+    }
 
-                for j in 0..i {
-                    if j == i {
-                        continue;
-                    }
-                    if p == &patterns[j] {
-                        // pattern is exactly same as something else. redo it.
-                        seed += 1;
-                        continue 'redo;
-                    }
-                }
+    /// Velocity of each track at a global step. The whole repeats every `pattern_length`
+    /// steps, and each track repeats at its own pattern length within that.
+    pub fn step(&self, global_step: usize) -> [u8; X] {
+        let step = global_step % (self.pattern_length() as usize).max(1);
 
-                break;
+        core::array::from_fn(|i| {
+            let p = &self.patterns[i];
+
+            if p.is_empty() {
+                0
+            } else {
+                p[step % p.len()]
             }
+        })
+    }
+
+    /// All tracks combined into one pattern of `pattern_length`, where a step is the max
+    /// velocity of any track.
+    pub fn combined(&self) -> Pattern {
+        let len = self.pattern_length() as usize;
+
+        self.patterns
+            .iter()
+            .fold(Pattern::new_with(0, len), |acc, p| {
+                acc.or(&p.repeat_to(len))
+            })
+    }
+
+    /// Parameters used to generate the patterns.
+    pub fn params(&self) -> &Params<X> {
+        &self.params
+    }
+
+    /// Iterate over the patterns together with the parameters for each track.
+    pub fn iter(&self) -> impl Iterator<Item = (&Pattern, &TrackParams)> {
+        self.patterns.iter().zip(self.params.tracks.iter())
+    }
+
+    /// Evolve the pattern of a single track by randomly flipping steps on or off. The
+    /// number of flipped steps is proportional to `amount`, where 255 is all steps.
+    ///
+    /// Uses the `rnd` of this instance, which means the other tracks are untouched.
+    pub fn mutate_track(&mut self, index: usize, amount: u8) {
+        let pattern = &mut self.patterns[index];
+        let len = pattern.len();
+
+        if amount == 0 || len == 0 {
+            return;
         }
 
-        // reserve 64 track specific rnd before letting it go.
-        for _ in X..=64 {
-            rnd.next();
+        let flips = ((len * amount as usize) / 255).max(1);
+
+        for _ in 0..flips {
+            let i = self.rnd.next_range(0..len as u32) as usize;
+
+            pattern[i] = if pattern[i] == 0 { 127 } else { 0 };
         }
+    }
+}
 
-        Generated {
-            pattern_length: params.pattern_length,
-            patterns,
-            rnd,
+/// Generate all track patterns into `patterns`. Returns the root randomizer
+/// after all tracks have been generated.
+fn generate_patterns<const X: usize>(params: &Params<X>, patterns: &mut [Pattern; X]) -> Rnd {
+    assert!(params.pattern_length > 0);
+
+    // Root randomizer.
+    let mut rnd = Rnd::new(params.seed);
+
+    for i in 0..X {
+        let mut seed = rnd.next() + (i as u32);
+        let mut redo_sanity_check = 0;
+
+        'redo: loop {
+            redo_sanity_check += 1;
+            if redo_sanity_check >= 50 {
+                panic!("generate: redo loop runaway");
+            }
+
+            // generate pattern for this index.
+            debug!(
+                "Generate track: {} seed: {} params: {:?}",
+                i, seed, &params.tracks[i]
+            );
+            patterns[i] = generate(
+                seed,
+                &params.tracks[i],
+                params.pattern_length as usize,
+                true,
+                true,
+            );
+
+            // if we are not randomizing, whatever we got is fine.
+            if params.tracks[i].steps > 0 {
+                break;
+            }
+
+            // since we randomized, ensure we didn't end up with something that is exactly
+            // like what we already have.
+            let p = &patterns[i];
+
+            for j in 0..i {
+                if j == i {
+                    continue;
+                }
+                if p == &patterns[j] {
+                    // pattern is exactly same as something else. redo it.
+                    seed += 1;
+                    continue 'redo;
+                }
+            }
+
+            break;
         }
     }
 
-    pub fn len(&self) -> usize {
-        X
+    // Muted tracks are generated as normal to keep everything else the same, but
+    // silenced afterwards.
+    for (pattern, track) in patterns.iter_mut().zip(params.tracks.iter()) {
+        if track.muted {
+            *pattern = Pattern::new_with(0, params.pattern_length as usize);
+        }
+    }
+
+    // reserve track specific rnd before letting it go.
+    for _ in X..=RESERVED_TRACKS {
+        rnd.next();
     }
+
+    rnd
 }
 
 fn generate(
@@ -256,9 +396,11 @@ fn generate(
                 }
 
                 // let's do it!
+                // The velocity spread is applied to the combined pattern.
                 let mut new_params = TrackParams {
                     length: *length as u8,
                     offset: 0,
+                    velocity_spread: 0,
                     ..*params
                 };
 
@@ -313,7 +455,9 @@ fn generate(
                     p1 + p2
                 };
 
-                return combined.offset(params.offset).repeat_to(pattern_length);
+                let pattern = combined.offset(params.offset).repeat_to(pattern_length);
+
+                return spread_velocity(pattern, params.velocity_spread, &mut rnd);
             }
         }
     }
@@ -363,9 +507,71 @@ fn generate(
         params.steps
     };
 
-    euclid(steps, length)
+    let pattern = euclid(steps, length)
         .offset(offset)
-        .repeat_to(pattern_length)
+        .repeat_to(pattern_length);
+
+    spread_velocity(pattern, params.velocity_spread, &mut rnd)
+}
+
+/// The steps that are hits in exactly one of two euclidean rhythms over the same
+/// `length`, which gives a syncopated pattern from two densities. Hits have velocity 127.
+///
+/// ```ignore
+/// euclid(4, 8): x-x-x-x-
+/// euclid(3, 8): x--x-x--
+/// euclid_xor:   --xxxxx-
+/// ```
+///
+/// Panics on invalid input, like [`euclid`].
+pub fn euclid_xor(a_steps: u8, b_steps: u8, length: u8) -> Pattern {
+    let mut p = euclid(a_steps, length).xor(&euclid(b_steps, length));
+
+    for i in 0..p.len() {
+        if p[i] > 0 {
+            p[i] = 127;
+        }
+    }
+
+    p
+}
+
+/// Randomly vary the velocity of each hit by up to `spread` around its current value.
+/// Silent steps are kept silent, and normal hits and accents stay within their own
+/// range. A value too close to the edge of its range is first moved inwards, so the
+/// spread is centered. I.e. a normal hit of 127 with a spread of 60 is 67..=127.
+fn spread_velocity(mut pattern: Pattern, spread: u8, rnd: &mut Rnd) -> Pattern {
+    if spread == 0 {
+        // Don't touch rnd to keep the output exactly the same.
+        return pattern;
+    }
+
+    for i in 0..pattern.len() {
+        if pattern[i] == 0 {
+            continue;
+        }
+
+        let half = (spread / 2) as i32;
+        let delta = rnd.next_range(0..spread as u32 + 1) as i32 - half;
+
+        // Keep normal hits normal, and accents accented.
+        let (lo, hi) = if pattern.velocity_at(i).is_accent() {
+            (128, 255)
+        } else {
+            (1, 127)
+        };
+
+        // Center the spread within the range.
+        let center = if lo + half <= hi - half {
+            (pattern[i] as i32).clamp(lo + half, hi - half)
+        } else {
+            (lo + hi) / 2
+        };
+
+        pattern[i] = (center + delta).clamp(lo, hi) as u8;
+    }
+
+    pattern
 }
 
 #[cfg(test)]
@@ -396,4 +602,174 @@ mod test {
             drums.play(1);
         }
     }
+
+    #[test]
+    fn iter_params() {
+        let g: Generated<4> = Generated::new(STOKAST_PARAMS);
+
+        assert_eq!(g.params(), &STOKAST_PARAMS);
+        assert_eq!(g.iter().count(), 4);
+
+        for (i, (p, t)) in g.iter().enumerate() {
+            assert_eq!(p, &g.patterns[i]);
+            assert_eq!(t, &STOKAST_PARAMS.tracks[i]);
+        }
+    }
+
+    #[test]
+    fn combined() {
+        let g = Generated::<2> {
+            patterns: ["x---x---".into(), "--X---".into()],
+            params: Params {
+                pattern_length: 8,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(g.combined(), "x-X-x---");
+    }
+
+    #[test]
+    fn reserved_rnd() {
+        let g: Generated<4> = Generated::new(STOKAST_PARAMS);
+
+        // One draw per track, and then past the rest of the reserve. 65 in total.
+        let mut rnd = Rnd::new(STOKAST_PARAMS.seed);
+        for _ in 0..=RESERVED_TRACKS {
+            rnd.next();
+        }
+
+        assert_eq!(g.rnd, rnd);
+    }
+
+    #[test]
+    fn step() {
+        let g = Generated::<2> {
+            patterns: ["X---".into(), "-x-".into()],
+            params: Params {
+                pattern_length: 6,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let steps: Vec<_> = (0..8).map(|i| g.step(i)).collect();
+
+        assert_eq!(
+            steps,
+            [
+                [255, 0],
+                [0, 127],
+                [0, 0],
+                [0, 0],
+                [255, 127],
+                [0, 0],
+                // Starts over at pattern_length.
+                [255, 0],
+                [0, 127],
+            ]
+        );
+    }
+
+    #[test]
+    fn muted_track() {
+        let g1: Generated<4> = Generated::new(STOKAST_PARAMS);
+
+        let mut params = STOKAST_PARAMS;
+        params.tracks[2].muted = true;
+        let g2: Generated<4> = Generated::new(params);
+
+        for i in [0, 1, 3] {
+            assert_eq!(g1.patterns[i], g2.patterns[i]);
+        }
+
+        assert_eq!(g2.patterns[2].len(), 64);
+        assert_eq!(g2.patterns[2].count(0..64), 0);
+        assert_eq!(g1.rnd, g2.rnd);
+    }
+
+    #[test]
+    fn mutate_track() {
+        let g: Generated<4> = Generated::new(STOKAST_PARAMS);
+
+        let mut m = g.clone();
+        m.mutate_track(1, 0);
+        assert_eq!(m, g);
+
+        m.mutate_track(1, 128);
+        assert_ne!(m.patterns[1], g.patterns[1]);
+        assert_eq!(m.patterns[1].len(), g.patterns[1].len());
+
+        for i in [0, 2, 3] {
+            assert_eq!(m.patterns[i], g.patterns[i]);
+        }
+    }
+
+    #[test]
+    fn reseed() {
+        let mut g: Generated<4> = Generated::new(STOKAST_PARAMS);
+
+        g.reseed(STOKAST_PARAMS.seed + 1);
+        assert_ne!(g, Generated::new(STOKAST_PARAMS));
+
+        g.reseed(STOKAST_PARAMS.seed);
+        assert_eq!(g, Generated::new(STOKAST_PARAMS));
+    }
+
+    #[test]
+    fn velocity_spread() {
+        let mut params: Params<1> = Params::default();
+        params.tracks[0] = TrackParams {
+            steps: 8,
+            length: 16,
+            velocity_spread: 60,
+            ..Default::default()
+        };
+
+        let g = Generated::new(params);
+        let p = g.patterns[0];
+
+        // Same rhythm as without spread.
+        params.tracks[0].velocity_spread = 0;
+        let plain = Generated::new(params).patterns[0];
+        assert_eq!(p.len(), plain.len());
+        for i in 0..p.len() {
+            assert_eq!(p[i] == 0, plain[i] == 0);
+            assert_eq!(
+                p.velocity_at(i).is_accent(),
+                plain.velocity_at(i).is_accent()
+            );
+        }
+
+        let first = (0..p.len()).map(|i| p[i]).find(|v| *v > 0).unwrap();
+        assert!((0..p.len()).any(|i| p[i] > 0 && p[i] != first));
+
+        // Spread around 97, not piled up at the ceiling of 127.
+        let hits: Vec<_> = (0..p.len()).map(|i| p[i]).filter(|v| *v > 0).collect();
+        assert!(hits.iter().all(|v| (67..=127).contains(v)), "{:?}", hits);
+        assert!(
+            hits.iter().filter(|v| **v == 127).count() <= 1,
+            "{:?}",
+            hits
+        );
+    }
+
+    #[test]
+    fn euclid_xor_stable() {
+        assert_eq!(euclid_xor(4, 3, 8), "|--xxxxx-|");
+        assert_eq!(euclid_xor(4, 3, 16), "|----x-x-x--xx---|");
+        assert!(euclid_xor(3, 3, 16).is_silent());
+    }
+
+    #[test]
+    fn seed_from_str_stable() {
+        assert_eq!(seed_from_str("acid"), seed_from_str("acid"));
+        assert_ne!(seed_from_str("acid"), seed_from_str("acie"));
+        assert_ne!(seed_from_str("acid"), seed_from_str("dica"));
+
+        // Pinned to be the same across platforms.
+        assert_eq!(seed_from_str(""), 0x811c_9dc5);
+        assert_eq!(seed_from_str("a"), 0xe40c_292c);
+    }
 }
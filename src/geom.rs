@@ -29,9 +29,35 @@ pub fn sin(angle: u32) -> i16 {
     }
 }
 
+/// Cosinus for an angle (going 0..u32::MAX for 0..360deg), where returned value is -32768 to 32767.
 pub fn cos(angle: u32) -> i16 {
-    sin(angle + 0x4000_0000)
+    sin(angle.wrapping_add(QUARTER))
 }
+
+/// A quarter turn (90deg) in the angle used by [`sin`] and [`cos`].
+pub const QUARTER: u32 = 0x4000_0000;
+
+/// A half turn (180deg) in the angle used by [`sin`] and [`cos`].
+pub const HALF: u32 = 0x8000_0000;
+
+/// Angle for [`sin`], [`cos`] and [`tri`] from degrees. Any degrees wrap around the
+/// full circle, i.e. -90.0 is the same as 270.0.
+pub fn from_degrees(deg: f32) -> u32 {
+    from_turns(deg / 360.0)
+}
+
+/// Angle for [`sin`], [`cos`] and [`tri`] from turns, where 1.0 is the full circle.
+/// Any turns wrap around the full circle, i.e. -0.25 is the same as 0.75.
+pub fn from_turns(turns: f32) -> u32 {
+    // Only the fractional part of the turn matters.
+    let mut fract = turns - (turns as i32) as f32;
+    if fract < 0.0 {
+        fract += 1.0;
+    }
+
+    (fract as f64 * (1_u64 << 32) as f64) as u64 as u32
+}
+
 const SIN_TABLE: &[u16] = &[
     0, 402, 804, 1206, 1608, 2010, 2412, 2813, 3215, 3617, 4018, 4419, 4821, 5221, 5622, 6023,
     6423, 6823, 7223, 7622, 8022, 8421, 8819, 9218, 9615, 10013, 10410, 10807, 11203, 11599, 11995,
@@ -73,6 +99,29 @@ pub fn tri(offset: u32) -> i16 {
     n as i16
 }
 
+/// Integer square root, rounded down.
+///
+/// This is the bit-by-bit (digit-by-digit) algorithm, with a fixed number of
+/// iterations and no branches in the loop.
+pub fn isqrt(n: u32) -> u32 {
+    let mut rem = n;
+    let mut root = 0_u32;
+    let mut bit = 1_u32 << 30;
+
+    for _ in 0..16 {
+        let trial = root + bit;
+
+        // All ones if the trial fits in the remainder, otherwise 0.
+        let mask = ((rem >= trial) as u32).wrapping_neg();
+
+        rem -= trial & mask;
+        root = (root >> 1) + (bit & mask);
+        bit >>= 2;
+    }
+
+    root
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -91,6 +140,21 @@ mod test {
         assert_eq!(sin(u32::MAX), 0);
     }
 
+    #[test]
+    fn test_cos() {
+        assert_eq!(cos(0), 32767);
+        assert!(cos(QUARTER).abs() <= 1);
+        assert_eq!(cos(QUARTER * 2), -32767);
+        assert!(cos(QUARTER * 3).abs() <= 1);
+
+        // No overflow on the last quarter.
+        assert!(cos(u32::MAX) > 32700);
+
+        for angle in [0, 12345, QUARTER / 3, QUARTER * 2 + 999] {
+            assert_eq!(cos(angle), sin(angle + QUARTER));
+        }
+    }
+
     #[test]
     fn test_tri() {
         assert_eq!(tri(0), 0);
@@ -100,4 +164,36 @@ mod test {
         assert_eq!(tri(u32::MAX), -1);
         assert_eq!(tri(3221225472), -32768);
     }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(2), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u32::MAX), 65535);
+
+        for i in 0..=65535_u32 {
+            assert_eq!(isqrt(i * i), i);
+        }
+
+        for i in 1..=65535_u32 {
+            assert_eq!(isqrt(i * i - 1), i - 1);
+        }
+    }
+
+    #[test]
+    fn test_from_degrees() {
+        assert_eq!(from_degrees(0.0), 0);
+        assert_eq!(from_degrees(90.0), QUARTER);
+        assert_eq!(from_degrees(180.0), HALF);
+        assert_eq!(from_degrees(-90.0), QUARTER * 3);
+        assert_eq!(from_degrees(450.0), QUARTER);
+        assert_eq!(from_turns(0.5), HALF);
+
+        assert!(sin(from_degrees(90.0)) > 32700);
+        assert!(sin(from_degrees(180.0)).abs() <= 1);
+        assert!(sin(from_degrees(270.0)) < -32700);
+    }
 }
@@ -9,6 +9,36 @@ pub trait DeltaInput<const CLK: u32> {
     fn tick(&mut self, now: Time<CLK>) -> i8;
 }
 
+/// Sum of two delta inputs, such as a coarse and a fine encoder adjusting the same value.
+pub struct SumDelta<A, B, const CLK: u32> {
+    a: A,
+    b: B,
+}
+
+impl<A, B, const CLK: u32> SumDelta<A, B, CLK>
+where
+    A: DeltaInput<CLK>,
+    B: DeltaInput<CLK>,
+{
+    pub fn new(a: A, b: B) -> Self {
+        SumDelta { a, b }
+    }
+}
+
+impl<A, B, const CLK: u32> DeltaInput<CLK> for SumDelta<A, B, CLK>
+where
+    A: DeltaInput<CLK>,
+    B: DeltaInput<CLK>,
+{
+    fn tick(&mut self, now: Time<CLK>) -> i8 {
+        // Always tick both to not starve any input.
+        let a = self.a.tick(now);
+        let b = self.b.tick(now);
+
+        a.saturating_add(b)
+    }
+}
+
 /// An input that is either high or low.
 pub trait DigitalInput<const CLK: u32>: Sized {
     /// Polled when needed.
@@ -23,6 +53,16 @@ pub trait DigitalInput<const CLK: u32>: Sized {
     fn edge(self) -> DigitalEdgeInput<Self, CLK> {
         DigitalEdgeInput::new(self)
     }
+
+    /// Turns this input into a rising edge sensing input.
+    fn rising(self) -> RisingEdge<Self, CLK> {
+        RisingEdge::new(self)
+    }
+
+    /// Turns this momentary input into a toggle that flips on each press.
+    fn latching(self) -> Latching<Self, CLK> {
+        Latching::new(self)
+    }
 }
 
 /// Digital input over reading a pointer to a shared number.
@@ -170,6 +210,213 @@ where
     }
 }
 
+/// Only the rising edges of a DigitalInput. Typical for trigger inputs.
+pub struct RisingEdge<I, const CLK: u32> {
+    edge: DigitalEdgeInput<I, CLK>,
+}
+
+impl<I, const CLK: u32> RisingEdge<I, CLK>
+where
+    I: DigitalInput<CLK>,
+{
+    pub fn new(input: I) -> Self {
+        RisingEdge {
+            edge: DigitalEdgeInput::new(input),
+        }
+    }
+
+    /// Polled when needed. Returns the time of the edge when going from low to high.
+    pub fn tick(&mut self, now: Time<CLK>) -> Option<Time<CLK>> {
+        match self.edge.tick(now) {
+            Some(Edge::Rising(t)) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+/// A momentary input acting as a toggle switch. Each rising edge flips the state,
+/// starting out low.
+pub struct Latching<I, const CLK: u32> {
+    edge: DigitalEdgeInput<I, CLK>,
+    state: HiLo<CLK>,
+}
+
+impl<I, const CLK: u32> Latching<I, CLK>
+where
+    I: DigitalInput<CLK>,
+{
+    pub fn new(input: I) -> Self {
+        Latching {
+            edge: DigitalEdgeInput::new(input),
+            state: HiLo::Lo(Time::ZERO),
+        }
+    }
+}
+
+impl<I, const CLK: u32> DigitalInput<CLK> for Latching<I, CLK>
+where
+    I: DigitalInput<CLK>,
+{
+    fn tick(&mut self, now: Time<CLK>) -> HiLo<CLK> {
+        if let Some(Edge::Rising(t)) = self.edge.tick(now) {
+            self.state = if self.state.is_set() {
+                HiLo::Lo(t)
+            } else {
+                HiLo::Hi(t)
+            };
+        }
+
+        self.state
+    }
+}
+
+/// Transport from a clock and a reset input, such as from modular gear.
+pub struct SyncInput<C, R, const CLK: u32> {
+    clock: C,
+    reset: R,
+    /// Clock pulses since the last reset.
+    count: u32,
+    /// A clock edge arrived together with a reset, emit it on the next tick.
+    pending: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncEvent {
+    /// A clock pulse with the number of pulses since the last reset, starting at 0.
+    Clock(u32),
+    /// The counter was reset. The next clock pulse is 0.
+    Reset,
+}
+
+impl<C, R, const CLK: u32> SyncInput<C, R, CLK>
+where
+    C: EdgeInput<CLK>,
+    R: EdgeInput<CLK>,
+{
+    pub fn new(clock: C, reset: R) -> Self {
+        SyncInput {
+            clock,
+            reset,
+            count: 0,
+            pending: false,
+        }
+    }
+
+    /// Polled when needed. A reset takes precedence over a clock pulse in the same tick,
+    /// in which case the pulse is emitted in the tick after.
+    pub fn tick(&mut self, now: Time<CLK>) -> Option<SyncEvent> {
+        let reset = self.reset.tick(now).map(|e| e.is_rising()).unwrap_or(false);
+        let clock = self.clock.tick(now).map(|e| e.is_rising()).unwrap_or(false);
+
+        if reset {
+            self.count = 0;
+            self.pending = clock;
+            return Some(SyncEvent::Reset);
+        }
+
+        if clock || self.pending {
+            self.pending = false;
+
+            let n = self.count;
+            self.count = self.count.wrapping_add(1);
+
+            return Some(SyncEvent::Clock(n));
+        }
+
+        None
+    }
+}
+
+/// An input of a continuous value, such as a potentiometer or CV.
+pub trait AnalogInput<const CLK: u32>: Sized {
+    /// Polled when needed.
+    fn tick(&mut self, now: Time<CLK>) -> u16;
+
+    /// Wrap this source input in an exponential moving average. See [`Smoothed`].
+    fn smoothed(self, alpha: f32) -> Smoothed<Self, CLK> {
+        Smoothed::new(self, alpha)
+    }
+}
+
+/// Analog input over reading a pointer to a shared number.
+///
+/// The value is the bits in the mask, shifted down to the lowest bit of the mask.
+pub struct BitmaskAnalogInput<W> {
+    word: *const W,
+    mask: W,
+    shift: u32,
+}
+
+impl<W> BitmaskAnalogInput<W>
+where
+    W: BitAnd<Output = W> + Copy + Into<u32>,
+{
+    /// Read the bits in `mask` from `word`.
+    ///
+    /// Panics if the mask is 0, or if the bits of the mask span more than 16 bits.
+    pub fn new(word: *const W, mask: W) -> Self {
+        let m: u32 = mask.into();
+        assert!(m != 0, "BitmaskAnalogInput mask must not be 0");
+
+        let shift = m.trailing_zeros();
+        assert!(
+            m >> shift <= u16::MAX as u32,
+            "BitmaskAnalogInput mask must span at most 16 bits"
+        );
+
+        BitmaskAnalogInput { word, mask, shift }
+    }
+}
+
+impl<W, const CLK: u32> AnalogInput<CLK> for BitmaskAnalogInput<W>
+where
+    W: BitAnd<Output = W> + Copy + Into<u32>,
+{
+    fn tick(&mut self, _now: Time<CLK>) -> u16 {
+        let v: u32 = unsafe { *self.word }.bitand(self.mask).into();
+        (v >> self.shift) as u16
+    }
+}
+
+/// Exponential moving average of an AnalogInput to smooth out jitter.
+///
+/// `alpha` is 0.0..=1.0, where a lower value is more smoothing, and 1.0 is no smoothing.
+pub struct Smoothed<I, const CLK: u32> {
+    input: I,
+    alpha: f32,
+    /// Current average. None before the first tick.
+    value: Option<f32>,
+}
+
+impl<I, const CLK: u32> Smoothed<I, CLK>
+where
+    I: AnalogInput<CLK>,
+{
+    pub fn new(input: I, alpha: f32) -> Self {
+        Smoothed {
+            input,
+            alpha: alpha.clamp(0.0, 1.0),
+            value: None,
+        }
+    }
+}
+
+impl<I, const CLK: u32> AnalogInput<CLK> for Smoothed<I, CLK>
+where
+    I: AnalogInput<CLK>,
+{
+    fn tick(&mut self, now: Time<CLK>) -> u16 {
+        let x = self.input.tick(now) as f32;
+
+        // Start at the first reading rather than ramping up from 0.
+        let value = self.value.get_or_insert(x);
+
+        *value += (x - *value) * self.alpha;
+
+        (*value + 0.5) as u16
+    }
+}
+
 // Boiler plate impls for ()
 
 impl<const CLK: u32> DeltaInput<CLK> for () {
@@ -190,18 +437,37 @@ impl<const CLK: u32> EdgeInput<CLK> for () {
     }
 }
 
+impl<const CLK: u32> AnalogInput<CLK> for () {
+    fn tick(&mut self, _now: Time<CLK>) -> u16 {
+        0
+    }
+}
+
 /// Input that debounces the input to avoid unintentional double clicks.
+///
+/// A change of state is only accepted once `window` has passed since the last change.
+/// The default window is 1ms.
 pub struct DebounceDigitalInput<I, const CLK: u32> {
     input: I,
     value: HiLo<CLK>,
+    window: Time<CLK>,
 }
 impl<I, const CLK: u32> DebounceDigitalInput<I, CLK>
 where
     I: DigitalInput<CLK>,
 {
-    pub fn new(mut input: I) -> Self {
+    pub fn new(input: I) -> Self {
+        Self::with_window(input, Time::from_millis(1))
+    }
+
+    /// Creates a debounced input with a custom debounce window.
+    pub fn with_window(mut input: I, window: Time<CLK>) -> Self {
         let value = input.tick(Time::ZERO);
-        DebounceDigitalInput { input, value }
+        DebounceDigitalInput {
+            input,
+            value,
+            window,
+        }
     }
 }
 
@@ -213,7 +479,7 @@ where
         let value = self.input.tick(now);
 
         if !self.value.is_same_state(&value) {
-            if now - *self.value.time() > Time::from_millis(1) {
+            if now - *self.value.time() > self.window {
                 self.value = value;
             }
         }
@@ -221,3 +487,190 @@ where
         return self.value;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    /// Delta input always giving the same value.
+    struct Fixed(i8);
+
+    impl DeltaInput<1000> for Fixed {
+        fn tick(&mut self, _now: Time<1000>) -> i8 {
+            self.0
+        }
+    }
+
+    /// Digital input reading a cell.
+    struct Pin<'a>(&'a Cell<bool>);
+
+    impl<'a> DigitalInput<1000> for Pin<'a> {
+        fn tick(&mut self, now: Time<1000>) -> HiLo<1000> {
+            if self.0.get() {
+                HiLo::Hi(now)
+            } else {
+                HiLo::Lo(now)
+            }
+        }
+    }
+
+    /// Analog input reading a cell.
+    struct Pot<'a>(&'a Cell<u16>);
+
+    impl<'a> AnalogInput<1000> for Pot<'a> {
+        fn tick(&mut self, _now: Time<1000>) -> u16 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn smoothed() {
+        let c = Cell::new(500);
+        let mut s = Pot(&c).smoothed(0.25);
+
+        // Constant passes through.
+        for _ in 0..10 {
+            assert_eq!(s.tick(Time::ZERO), 500);
+        }
+
+        // Converges toward a step.
+        c.set(1000);
+        let mut prev = 500;
+        for _ in 0..10 {
+            let v = s.tick(Time::ZERO);
+            assert!(v > prev && v <= 1000);
+            prev = v;
+        }
+        for _ in 0..100 {
+            s.tick(Time::ZERO);
+        }
+        assert_eq!(s.tick(Time::ZERO), 1000);
+    }
+
+    #[test]
+    fn bitmask_analog() {
+        let word: u16 = 0b1011_0110;
+        let mut a = BitmaskAnalogInput::new(&word, 0b1111_0000);
+        assert_eq!(AnalogInput::<1000>::tick(&mut a, Time::ZERO), 0b1011);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitmask_analog_zero_mask() {
+        let word: u16 = 0;
+        BitmaskAnalogInput::new(&word, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitmask_analog_wide_mask() {
+        let word: u32 = 0;
+        BitmaskAnalogInput::new(&word, 0x0001_ffff);
+    }
+
+    #[test]
+    fn debounce_window() {
+        let c = Cell::new(false);
+        let mut d = DebounceDigitalInput::with_window(Pin(&c), Time::from_millis(10));
+
+        c.set(true);
+        assert!(d.tick(Time::from_millis(20)).is_set());
+
+        // Bounce within the window is rejected.
+        c.set(false);
+        assert!(d.tick(Time::from_millis(25)).is_set());
+
+        // Stable change past the window is accepted.
+        assert!(!d.tick(Time::from_millis(31)).is_set());
+    }
+
+    #[test]
+    fn rising_edge() {
+        let c = Cell::new(false);
+        let mut r = Pin(&c).rising();
+
+        let mut edges = vec![];
+
+        for (i, v) in [false, true, false, true].into_iter().enumerate() {
+            c.set(v);
+            if let Some(t) = r.tick(Time::new(i as i64 + 1)) {
+                edges.push(t.count());
+            }
+        }
+
+        assert_eq!(edges, [2, 4]);
+    }
+
+    #[test]
+    fn sync_input() {
+        let clk = Cell::new(false);
+        let rst = Cell::new(false);
+
+        let mut s = SyncInput::new(
+            DigitalEdgeInput::new(Pin(&clk)),
+            DigitalEdgeInput::new(Pin(&rst)),
+        );
+
+        let mut events = vec![];
+        let mut t = 0;
+
+        let mut step = |c: bool, r: bool| {
+            clk.set(c);
+            rst.set(r);
+            t += 1;
+            if let Some(e) = s.tick(Time::new(t)) {
+                events.push(e);
+            }
+        };
+
+        for _ in 0..3 {
+            step(true, false);
+            step(false, false);
+        }
+
+        step(false, true);
+        step(false, false);
+        step(true, false);
+
+        use SyncEvent::*;
+        assert_eq!(events, [Clock(0), Clock(1), Clock(2), Reset, Clock(0)]);
+    }
+
+    #[test]
+    fn latching() {
+        let c = Cell::new(false);
+        let mut l = Pin(&c).latching();
+
+        let mut states = vec![];
+        let mut t = 0;
+
+        for _ in 0..4 {
+            for v in [true, false] {
+                c.set(v);
+                t += 1;
+                let s = l.tick(Time::new(t));
+                if v {
+                    states.push(s.is_set());
+                } else {
+                    // Releasing doesn't change the state.
+                    assert_eq!(s.is_set(), *states.last().unwrap());
+                }
+            }
+        }
+
+        assert_eq!(states, [true, false, true, false]);
+    }
+
+    #[test]
+    fn sum_delta() {
+        let mut s = SumDelta::new(Fixed(1), Fixed(-1));
+        assert_eq!(s.tick(Time::ZERO), 0);
+
+        let mut s = SumDelta::new(Fixed(1), Fixed(1));
+        assert_eq!(s.tick(Time::ZERO), 2);
+
+        let mut s = SumDelta::new(Fixed(100), Fixed(100));
+        assert_eq!(s.tick(Time::ZERO), i8::MAX);
+    }
+}
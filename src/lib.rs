@@ -20,8 +20,11 @@ pub mod ring_buf;
 pub mod rnd;
 pub mod tempo;
 
-// #[cfg(feature = "float")]
-// pub mod wave;
+#[cfg(feature = "float")]
+pub mod adsr;
+
+#[cfg(feature = "float")]
+pub mod wave;
 
 #[cfg(test)]
 mod drums;
@@ -44,3 +47,17 @@ impl SetBit for u16 {
         *self & (1 << bit) > 0
     }
 }
+
+impl SetBit for u64 {
+    fn set_bit(&mut self, bit: u8, on: bool) {
+        if on {
+            *self |= 1 << bit;
+        } else {
+            *self &= !(1 << bit);
+        }
+    }
+
+    fn is_bit(&self, bit: u8) -> bool {
+        *self & (1 << bit) > 0
+    }
+}
@@ -28,18 +28,50 @@ pub fn translate<const L: usize>(s: &str, v: &mut Vec<u8, L>) {
     }
 }
 
+/// Render a row of an 8 pixel wide display. See [`render_wide`].
 pub fn render(
     row_index: usize,
-    mut char_index: usize,
+    char_index: usize,
     char_offset: u8,
     chars: &[u8],
     next_index: &mut bool,
 ) -> u8 {
+    // The 8 pixels to the left are the same as the upper half of a wide display.
+    (render_wide(row_index, char_index, char_offset, chars, next_index) >> 8) as u8
+}
+
+/// Render a row of an 8 pixel wide display mounted mirrored, which means the text
+/// reads right-to-left.
+///
+/// This is [`render`] with the bit order reversed: the first char is at the right most
+/// pixel (the lowest bit), each glyph is mirrored, and `char_offset` scrolls the text
+/// to the right.
+pub fn render_rtl(
+    row_index: usize,
+    char_index: usize,
+    char_offset: u8,
+    chars: &[u8],
+    next_index: &mut bool,
+) -> u8 {
+    render(row_index, char_index, char_offset, chars, next_index).reverse_bits()
+}
+
+/// Render a row of a 16 pixel wide display, starting at `char_index` shifted `char_offset`
+/// pixels to the left. The left most pixel is the highest bit.
+///
+/// `next_index` is set when the first char is scrolled entirely out of view.
+pub fn render_wide(
+    row_index: usize,
+    mut char_index: usize,
+    char_offset: u8,
+    chars: &[u8],
+    next_index: &mut bool,
+) -> u16 {
     let mut draw_position: i8 = 0;
     let mut extra_left_shift = char_offset;
 
-    let mut output: u8 = 0;
-    const OUTPUT_BITS: u8 = 8;
+    let mut output: u16 = 0;
+    const OUTPUT_BITS: u8 = 16;
 
     loop {
         if char_index >= chars.len() {
@@ -53,16 +85,16 @@ pub fn render(
 
         let alpha_index = chars[char_index] as usize;
         let char = &ALPHABET[alpha_index];
-        let space = if alpha_index == 36 { 2 } else { SPACE };
+        let space = space_after(alpha_index);
 
         let left_shift = (OUTPUT_BITS - char.width() + extra_left_shift) as i8 - draw_position;
 
-        if left_shift >= 8 {
+        if left_shift >= OUTPUT_BITS as i8 {
             *next_index = true;
         }
 
-        let row = char.rows()[row_index];
-        if left_shift.abs() < 8 {
+        let row = char.rows()[row_index] as u16;
+        if left_shift.abs() < OUTPUT_BITS as i8 {
             let shifted = if left_shift >= 0 {
                 row << left_shift
             } else {
@@ -79,6 +111,107 @@ pub fn render(
     output
 }
 
+/// Width in pixels of the translated `chars` as laid out by [`render`]. There is
+/// no space after the last char.
+pub fn text_width(chars: &[u8]) -> usize {
+    let mut width = 0;
+
+    for (i, c) in chars.iter().enumerate() {
+        let alpha_index = *c as usize;
+
+        width += ALPHABET[alpha_index].width() as usize;
+
+        if i < chars.len() - 1 {
+            width += space_after(alpha_index) as usize;
+        }
+    }
+
+    width
+}
+
+/// Marquee scrolling text one pixel at a time over an 8 pixel wide display.
+///
+/// Once the text and a gap has scrolled past, the scroll loops around.
+pub struct Scroller<'a> {
+    /// Translated chars. See [`translate`].
+    chars: &'a [u8],
+    /// Number of pixels of the text and the gap before it loops.
+    period: usize,
+    /// Current pixel offset.
+    offset: usize,
+}
+
+impl<'a> Scroller<'a> {
+    /// Create a scroller over translated `chars`, with `gap` pixels between the end and
+    /// start of the text when looping.
+    pub fn new(chars: &'a [u8], gap: usize) -> Self {
+        Scroller {
+            chars,
+            period: (text_width(chars) + gap).max(1),
+            offset: 0,
+        }
+    }
+
+    /// The 5 rows of the frame for the current offset. Advances one pixel per call.
+    pub fn next_frame(&mut self) -> [u8; 5] {
+        let mut frame = [0; 5];
+
+        // Draw each loop of the text that is visible in the display.
+        let mut start = -(self.offset as isize);
+
+        while start < 8 {
+            for (row_index, row) in frame.iter_mut().enumerate() {
+                *row |= render_at(row_index, self.chars, start);
+            }
+            start += self.period as isize;
+        }
+
+        self.offset = (self.offset + 1) % self.period;
+
+        frame
+    }
+}
+
+/// Render a row with the start of the text at pixel `x`. Negative is to the left
+/// of the display.
+fn render_at(row_index: usize, chars: &[u8], x: isize) -> u8 {
+    if x >= 8 {
+        return 0;
+    }
+
+    if x >= 0 {
+        return render(row_index, 0, 0, chars, &mut false) >> x;
+    }
+
+    // Skip whole chars to keep the char offset small.
+    let mut char_index = 0;
+    let mut char_offset = (-x) as usize;
+
+    while char_index < chars.len() {
+        let alpha_index = chars[char_index] as usize;
+        let step = (ALPHABET[alpha_index].width() + space_after(alpha_index)) as usize;
+
+        if char_offset < step {
+            break;
+        }
+
+        char_offset -= step;
+        char_index += 1;
+    }
+
+    render(row_index, char_index, char_offset as u8, chars, &mut false)
+}
+
+/// Space in pixels after the char. The space char gets a bit more.
+#[inline(always)]
+fn space_after(alpha_index: usize) -> u8 {
+    if alpha_index == 36 {
+        2
+    } else {
+        SPACE
+    }
+}
+
 struct Char(u8, [u8; 5]);
 
 impl Char {
@@ -486,13 +619,69 @@ mod test {
         assert_eq!(&v, &[]);
     }
 
+    #[test]
+    fn text_width_simple() {
+        let mut v: Vec<u8, 64> = Vec::new();
+
+        translate("ABC", &mut v);
+        assert_eq!(text_width(&v), 3 + 1 + 3 + 1 + 2);
+
+        translate("A B", &mut v);
+        // The space char is 0 wide, but has 2 space after.
+        assert_eq!(text_width(&v), 3 + 1 + 2 + 3);
+
+        translate("", &mut v);
+        assert_eq!(text_width(&v), 0);
+    }
+
+    #[test]
+    fn scroller_cycle() {
+        let mut v: Vec<u8, 64> = Vec::new();
+        translate("ABC", &mut v);
+
+        let mut scroller = Scroller::new(&v, 8);
+
+        let first = scroller.next_frame();
+
+        // Same as render without offset.
+        for (i, row) in first.iter().enumerate() {
+            assert_eq!(*row, render(i, 0, 0, &v, &mut false));
+        }
+
+        // Empty when text is fully scrolled past.
+        let period = text_width(&v) + 8;
+        let frames: Vec<_, 64> = (1..period).map(|_| scroller.next_frame()).collect();
+
+        // Skipping whole chars is the same as a big offset.
+        for (i, row) in frames[4].iter().enumerate() {
+            assert_eq!(*row, render(i, 0, 5, &v, &mut false));
+        }
+
+        assert_eq!(frames[text_width(&v) - 1], [0; 5]);
+
+        // Entering from the right before looping.
+        assert_eq!(frames[period - 2], first.map(|r| r >> 1));
+
+        assert_eq!(scroller.next_frame(), first);
+    }
+
     fn test_render(s: &str, i: usize, o: u8, c: &[&str]) {
+        test_render_with(render, s, i, o, c);
+    }
+
+    fn test_render_with(
+        f: fn(usize, usize, u8, &[u8], &mut bool) -> u8,
+        s: &str,
+        i: usize,
+        o: u8,
+        c: &[&str],
+    ) {
         let mut v: Vec<u8, 64> = Vec::new();
         translate(s, &mut v);
 
         let rows: Vec<_, 64> = (0..5)
             .map(|row| {
-                let o = render(row, i, o, &v, &mut false);
+                let o = f(row, i, o, &v, &mut false);
                 let s = format!("{:#010b}|", o).replace("0b", "|").replace("0", " ");
                 println!("{}", s);
                 s
@@ -518,6 +707,40 @@ mod test {
         );
     }
 
+    #[test]
+    fn render_rtl_no_offset() {
+        test_render_with(
+            render_rtl,
+            "ABC",
+            0,
+            0,
+            &[
+                "|  11 111|",
+                "| 1 1 1 1|",
+                "|  11 111|",
+                "| 1 1 1 1|",
+                "|  11 1 1|",
+            ],
+        );
+    }
+
+    #[test]
+    fn render_rtl_offset_1() {
+        test_render_with(
+            render_rtl,
+            "ABC",
+            0,
+            1,
+            &[
+                "|1  11 11|",
+                "|1 1 1 1 |",
+                "|1  11 11|",
+                "|1 1 1 1 |",
+                "|1  11 1 |",
+            ],
+        );
+    }
+
     #[test]
     fn render_offset_1() {
         test_render(
@@ -725,4 +948,68 @@ mod test {
             ],
         );
     }
+
+    fn test_render_wide(s: &str, i: usize, o: u8, c: &[&str]) {
+        let mut v: Vec<u8, 64> = Vec::new();
+        translate(s, &mut v);
+
+        let rows: Vec<_, 64> = (0..5)
+            .map(|row| {
+                let o = render_wide(row, i, o, &v, &mut false);
+                let s = format!("{:#018b}|", o).replace("0b", "|").replace("0", " ");
+                println!("{}", s);
+                s
+            })
+            .collect();
+
+        assert_eq!(&rows, c);
+    }
+
+    #[test]
+    fn render_wide_no_offset() {
+        test_render_wide(
+            "ABC",
+            0,
+            0,
+            &[
+                "|111 11  11      |",
+                "|1 1 1 1 1       |",
+                "|111 11  1       |",
+                "|1 1 1 1 1       |",
+                "|1 1 11  11      |",
+            ],
+        );
+    }
+
+    #[test]
+    fn render_wide_offset_4() {
+        test_render_wide(
+            "ABC",
+            0,
+            4,
+            &[
+                "|11  11          |",
+                "|1 1 1           |",
+                "|11  1           |",
+                "|1 1 1           |",
+                "|11  11          |",
+            ],
+        );
+    }
+
+    #[test]
+    fn render_wide_offset_1_3() {
+        test_render_wide(
+            "ABCD",
+            1,
+            3,
+            &[
+                "| 11 11          |",
+                "| 1  1 1         |",
+                "| 1  1 1         |",
+                "| 1  1 1         |",
+                "| 11 11          |",
+            ],
+        );
+    }
 }
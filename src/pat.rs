@@ -1,5 +1,10 @@
 use core::ops::Range;
 
+use crate::clock::Time;
+use crate::euclid::euclid;
+use crate::rnd::Rnd;
+use crate::SetBit;
+
 const MAX_LEN: usize = 64;
 
 /// A rhythmical pattern. Each step is a `u8` representing the velocity of that step.
@@ -32,6 +37,11 @@ where
         self.1
     }
 
+    /// Whether the pattern has length 0.
+    pub fn is_empty(&self) -> bool {
+        self.1 == 0
+    }
+
     /// Append a value to the pattern. Increases length by 1.
     pub fn push(&mut self, val: T) {
         assert!(self.1 + 1 <= MAX_LEN);
@@ -56,6 +66,33 @@ where
         Pat(p, len)
     }
 
+    /// Split the pattern in two at `index`. An index past the end gives the whole
+    /// pattern and an empty one.
+    ///
+    /// ```ignore
+    /// Start:   xA-BxA-Bx-
+    /// Split 4: xA-B xA-Bx-
+    /// ```
+    pub fn split_at(&self, index: usize) -> (Self, Self) {
+        let index = index.min(self.1);
+        (self.sub(0..index), self.sub(index..self.1))
+    }
+
+    /// Split the pattern into successive sub patterns of `size`, where the last might be
+    /// shorter. A `size` of 0 gives no sub patterns.
+    ///
+    /// ```ignore
+    /// Start:   xA-BxA-Bx-
+    /// Chunk 4: xA-B xA-B x-
+    /// ```
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = Self> {
+        let p = *self;
+
+        let n = if size == 0 { 0 } else { p.1.div_ceil(size) };
+
+        (0..n).map(move |i| p.sub(i * size..(i + 1) * size))
+    }
+
     /// Get the value at `index`.
     pub fn get(&self, index: usize) -> Option<T> {
         if index < self.1 {
@@ -66,7 +103,15 @@ where
     }
 
     /// Set the value at `index`.
+    ///
+    /// The index wraps around the length of the pattern, also for negative indexes.
+    /// I.e. for a pattern of length 4, index 5 sets position 1 and index -1 sets
+    /// position 3. Setting on a zero length pattern does nothing.
     pub fn set(&mut self, mut index: isize, v: T) {
+        if self.1 == 0 {
+            return;
+        }
+
         while index < 0 {
             index += self.1 as isize;
         }
@@ -84,9 +129,54 @@ where
     /// Offset 5: B-CD---A---
     /// ```
     pub fn offset(&self, offset: u8) -> Self {
+        let mut p = *self;
+        p.offset_mut(offset);
+        p
+    }
+
+    /// In place version of `offset()`.
+    pub fn offset_mut(&mut self, offset: u8) {
+        // Values past the length are default, like in a pattern built up by push().
+        self.0[self.1..].fill(T::default());
+
+        if self.1 == 0 {
+            return;
+        }
+
         let m = (offset as usize) % self.1;
-        let p = self.1 - m;
-        self.sub(p..self.1) + self.sub(0..p)
+        self.0[..self.1].rotate_right(m);
+    }
+
+    /// Make a copy of self with the values in reverse order.
+    ///
+    /// ```ignore
+    /// Start:   --A---B-CD-
+    /// Reverse: -DC-B---A--
+    /// ```
+    pub fn reverse(&self) -> Self {
+        let mut p = *self;
+        p.reverse_mut();
+        p
+    }
+
+    /// In place version of `reverse()`.
+    pub fn reverse_mut(&mut self) {
+        self.0[..self.1].reverse();
+    }
+
+    /// Like `offset()`, but by a fraction `num / den` of the length, rounded to the
+    /// nearest step. `rotate_fraction(1, 4)` moves the pattern by a quarter.
+    pub fn rotate_fraction(&self, num: u32, den: u32) -> Self {
+        if self.1 == 0 || den == 0 {
+            return *self;
+        }
+
+        let len = self.1 as u64;
+        let den = den as u64;
+
+        let steps = (len * num as u64 + den / 2) / den;
+
+        self.offset((steps % len) as u8)
     }
 
     /// Lengthen the pattern by repeating what is already there to an absolute `len`.
@@ -97,12 +187,20 @@ where
     /// Repeat 12: xA-BxA-BxA-B
     /// ```
     pub fn repeat_to(&self, len: usize) -> Self {
-        assert!(len <= MAX_LEN);
+        self.try_repeat_to(len)
+            .expect("repeat_to len must be <= MAX_LEN")
+    }
+
+    /// Like `repeat_to()`, but returns `None` if `len` is greater than `MAX_LEN`.
+    pub fn try_repeat_to(&self, len: usize) -> Option<Self> {
+        if len > MAX_LEN {
+            return None;
+        }
 
         let mut x = Self::new();
 
         if self.1 == 0 {
-            return x;
+            return Some(x);
         }
 
         let n = len / self.1;
@@ -113,6 +211,30 @@ where
         }
         x += self.sub(0..m);
 
+        Some(x)
+    }
+
+    /// Stretch or compress the pattern to `len` by picking the nearest step. Upsampling
+    /// duplicates steps rather than interpolating.
+    ///
+    /// ```ignore
+    /// Start:      xA-B
+    /// Resample 2: x-
+    /// Resample 8: xxAA--BB
+    /// ```
+    pub fn resample(&self, len: usize) -> Self {
+        assert!(len <= MAX_LEN);
+
+        let mut x = Self::new();
+
+        if self.1 == 0 {
+            return x;
+        }
+
+        for i in 0..len {
+            x.push(self.0[i * self.1 / len]);
+        }
+
         x
     }
 }
@@ -200,11 +322,499 @@ where
     }
 }
 
+/// Panics if the iterator has more than `MAX_LEN` elements.
+impl<T> FromIterator<T> for Pat<T>
+where
+    T: Copy + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut p = Pat::new();
+
+        for v in iter {
+            p.push(v);
+        }
+
+        p
+    }
+}
+
 impl Pat<u8> {
     pub fn density(&self) -> u8 {
         let x = self.repeat_to(64);
         x.0.iter().filter(|x| **x > 0).count() as u8 * 2
     }
+
+    /// Velocity of the step at `index`. Steps outside the length are off.
+    pub fn velocity_at(&self, index: usize) -> Velocity {
+        Velocity(self.get(index).unwrap_or(0))
+    }
+
+    /// Whether the pattern has a length, but no hits.
+    pub fn is_silent(&self) -> bool {
+        self.1 > 0 && self.0[..self.1].iter().all(|v| *v == 0)
+    }
+
+    /// Construct a pattern of `len` from a bitmask, where step `i` is `vel` if bit `i` is set.
+    pub fn from_bits(bits: u64, len: usize, vel: u8) -> Self {
+        assert!(
+            len <= MAX_LEN,
+            "Pattern from bits len {} > {}",
+            len,
+            MAX_LEN
+        );
+
+        let mut p = Pattern::new();
+
+        for i in 0..len {
+            p.push(if bits.is_bit(i as u8) { vel } else { 0 });
+        }
+
+        p
+    }
+
+    /// Bitmask of the pattern, where bit `i` is set if step `i` is non-zero.
+    pub fn to_bits(&self) -> u64 {
+        let mut bits = 0_u64;
+
+        for i in 0..self.1 {
+            bits.set_bit(i as u8, self.0[i] > 0);
+        }
+
+        bits
+    }
+
+    /// Count the number of hits (non-zero steps) in the `range`. The range is
+    /// clamped to the length of the pattern.
+    pub fn count(&self, range: Range<usize>) -> usize {
+        let to = self.1.min(range.end);
+
+        if range.start >= to {
+            return 0;
+        }
+
+        self.0[range.start..to].iter().filter(|x| **x > 0).count()
+    }
+
+    /// Whether the patterns have the same hits, regardless of velocity.
+    pub fn eq_rhythm(&self, other: &Pattern) -> bool {
+        self.1 == other.1 && self.to_bits() == other.to_bits()
+    }
+
+    /// Blend the velocities of two patterns, where `w` 0.0 is `self` and 1.0 is `other`.
+    /// If the lengths differ, the shorter pattern is repeated to the longer.
+    pub fn blend(&self, other: &Pattern, w: f32) -> Pattern {
+        let len = self.1.max(other.1);
+        let (a, b) = (self.repeat_to(len), other.repeat_to(len));
+
+        let w = w.clamp(0.0, 1.0);
+
+        let mut p = Pattern::new();
+
+        for i in 0..len {
+            let (v1, v2) = (a.0[i] as f32, b.0[i] as f32);
+
+            // weighted value between the patterns, rounded.
+            let v = v1 + (v2 - v1) * w;
+            p.push((v + 0.5) as u8);
+        }
+
+        p
+    }
+
+    /// Combine the hits of two patterns, where each step is the max velocity of the two.
+    /// If the lengths differ, the shorter pattern is repeated to the longer.
+    pub fn or(&self, other: &Pattern) -> Pattern {
+        self.merge(other, MergePolicy::Max)
+    }
+
+    /// Keep the hits that are in only one of the two patterns, with their velocity.
+    /// Steps that are hits in both go silent. If the lengths differ, the shorter pattern
+    /// is repeated to the longer.
+    pub fn xor(&self, other: &Pattern) -> Pattern {
+        let len = self.1.max(other.1);
+        let (a, b) = (self.repeat_to(len), other.repeat_to(len));
+
+        let mut p = Pattern::new();
+
+        for i in 0..len {
+            let v = match (a.0[i], b.0[i]) {
+                (0, v) | (v, 0) => v,
+                _ => 0,
+            };
+
+            p.push(v);
+        }
+
+        p
+    }
+
+    /// Combine the hits of two patterns, where steps that are hits in both get a velocity
+    /// according to the `policy`. If the lengths differ, the shorter pattern is repeated
+    /// to the longer.
+    pub fn merge(&self, other: &Pattern, policy: MergePolicy) -> Pattern {
+        let len = self.1.max(other.1);
+        let (a, b) = (self.repeat_to(len), other.repeat_to(len));
+
+        let mut p = Pattern::new();
+
+        for i in 0..len {
+            let (v1, v2) = (a.0[i], b.0[i]);
+
+            let v = if v1 == 0 {
+                v2
+            } else if v2 == 0 {
+                v1
+            } else {
+                match policy {
+                    MergePolicy::Max => v1.max(v2),
+                    MergePolicy::Add => v1.saturating_add(v2),
+                    MergePolicy::Overwrite => v2,
+                    MergePolicy::KeepSelf => v1,
+                }
+            };
+
+            p.push(v);
+        }
+
+        p
+    }
+
+    /// Rotate the pattern so the first hit is on step 0. A silent pattern is
+    /// returned as is.
+    ///
+    /// ```ignore
+    /// Start:      --x--x-x
+    /// Normalized: x--x-x--
+    /// ```
+    pub fn normalize_rotation(&self) -> Pattern {
+        match self.next_hit(0) {
+            Some(i) if i > 0 => self.offset((self.1 - i) as u8),
+            _ => *self,
+        }
+    }
+
+    /// Index of the next hit (non-zero step) at or after `from`.
+    pub fn next_hit(&self, from: usize) -> Option<usize> {
+        (from..self.1).find(|i| self.0[*i] > 0)
+    }
+
+    /// Index of the next hit (non-zero step) at or after `from`, wrapping around to
+    /// the start of the pattern. `None` if there are no hits.
+    pub fn next_hit_wrapping(&self, from: usize) -> Option<usize> {
+        self.next_hit(from).or_else(|| self.next_hit(0))
+    }
+
+    /// Multiply the velocity of each hit by `factor`. Hits are clamped to 1..=255, which
+    /// means they never go silent and the rhythm is kept.
+    pub fn scale_velocity(&self, factor: f32) -> Pattern {
+        let mut p = *self;
+
+        for i in 0..p.1 {
+            let v = p.0[i];
+
+            if v == 0 {
+                continue;
+            }
+
+            let scaled = v as f32 * factor + 0.5;
+            p.0[i] = scaled.clamp(1.0, 255.0) as u8;
+        }
+
+        p
+    }
+
+    /// Set the velocity of each hit by linear interpolation over the step index, from
+    /// `start` at the first hit to `end` at the last hit. Hits never go below 1, which
+    /// means the rhythm is kept. Use for builds where later hits are louder.
+    pub fn ramp_velocity(&self, start: u8, end: u8) -> Pattern {
+        let mut p = *self;
+
+        let Some(first) = self.next_hit(0) else {
+            return p;
+        };
+        let last = (0..self.1).rev().find(|i| self.0[*i] > 0).unwrap_or(first);
+
+        let span = (last - first).max(1) as f32;
+        let (start, end) = (start as f32, end as f32);
+
+        for i in first..=last {
+            if p.0[i] == 0 {
+                continue;
+            }
+
+            let w = (i - first) as f32 / span;
+            let v = start + (end - start) * w + 0.5;
+            p.0[i] = v.clamp(1.0, 255.0) as u8;
+        }
+
+        p
+    }
+
+    /// Raise the velocity to `boosted` for hits that coincide with hits in `template`.
+    /// The template is repeated (or cut) to the length of this pattern.
+    ///
+    /// ```ignore
+    /// Start:    x-x-x-x-
+    /// Template: X---
+    /// Accent:   X-x-X-x-
+    /// ```
+    pub fn accent(&self, template: &Pattern, boosted: u8) -> Pattern {
+        let mut p = *self;
+
+        if template.1 == 0 {
+            return p;
+        }
+
+        let t = template.repeat_to(self.1);
+
+        for i in 0..p.1 {
+            if p.0[i] > 0 && t.0[i] > 0 {
+                p.0[i] = boosted;
+            }
+        }
+
+        p
+    }
+
+    /// Redistribute the hits euclidean style to get `target_hits` over the same length.
+    /// The target is clamped to the length of the pattern.
+    ///
+    /// Hits that coincide with the existing keep their velocity. This is for gradually
+    /// filling (or thinning) a pattern live.
+    ///
+    /// ```ignore
+    /// Start:    x-------x-------
+    /// Fill 4:   x---x---x---x---
+    /// ```
+    pub fn fill_to(&self, target_hits: usize) -> Self {
+        let target = target_hits.min(self.1);
+
+        if target == 0 {
+            return Pattern::new_with(0, self.1);
+        }
+
+        let e = euclid(target as u8, self.1 as u8);
+
+        let mut p = Pattern::new();
+
+        for i in 0..self.1 {
+            p.push(match (e.0[i], self.0[i]) {
+                (0, _) => 0,
+                (x, 0) => x,
+                (_, v) => v,
+            });
+        }
+
+        p
+    }
+
+    /// Randomly jitter velocity and timing of each hit, without changing the pattern.
+    ///
+    /// * `vel_amount` is the max velocity change up or down.
+    /// * `timing_amount` is the max timing change early or late in 1/256 of a step.
+    ///
+    /// Silent steps are kept silent, and hits never go silent.
+    pub fn humanize(&self, rnd: &mut Rnd, vel_amount: u8, timing_amount: u8) -> HumanizedPattern {
+        let mut h = HumanizedPattern {
+            pattern: *self,
+            velocity: [0; MAX_LEN],
+            timing: [0.0; MAX_LEN],
+        };
+
+        for i in 0..self.1 {
+            let v = self.0[i] as i16;
+
+            if v == 0 {
+                continue;
+            }
+
+            let vel = vel_amount as u32;
+            let delta = rnd.next_range(0..vel * 2 + 1) as i16 - vel as i16;
+            h.velocity[i] = (v + delta).clamp(1, 255) - v;
+
+            let timing = timing_amount as u32;
+            let delta = rnd.next_range(0..timing * 2 + 1) as i32 - timing as i32;
+            h.timing[i] = delta as f32 / 256.0;
+        }
+
+        h
+    }
+}
+
+/// A pattern with jitter of velocity and timing per step. See [`Pattern::humanize`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HumanizedPattern {
+    /// The pattern without jitter.
+    pub pattern: Pattern,
+    /// Velocity change per step.
+    velocity: [i16; MAX_LEN],
+    /// Timing offset per step in fractional steps.
+    timing: [f32; MAX_LEN],
+}
+
+impl HumanizedPattern {
+    /// Velocity at `index` with the jitter applied.
+    pub fn velocity(&self, index: usize) -> u8 {
+        (self.pattern[index] as i16 + self.velocity[index]) as u8
+    }
+
+    /// Velocity change at `index`.
+    pub fn velocity_delta(&self, index: usize) -> i16 {
+        self.velocity[index]
+    }
+
+    /// Timing offset at `index` as a fraction of a step. Negative is early.
+    pub fn timing(&self, index: usize) -> f32 {
+        self.timing[index]
+    }
+}
+
+/// A pattern where each step fires with some probability. This is for variation
+/// on every pass of a loop, like "trig conditions" in some drum machines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbPattern {
+    /// The pattern when every step fires.
+    pub pattern: Pattern,
+    /// Probability per step 0..=100.
+    pub probability: [u8; MAX_LEN],
+}
+
+impl ProbPattern {
+    /// Construct a new probability pattern where every step always fires.
+    pub fn new(pattern: Pattern) -> Self {
+        ProbPattern {
+            pattern,
+            probability: [100; MAX_LEN],
+        }
+    }
+
+    /// Roll each step to produce the concrete pattern for one pass.
+    ///
+    /// Steps with probability 100 or more always fire, and `rnd` is only used for
+    /// hits with a probability below that.
+    pub fn resolve(&self, rnd: &mut Rnd) -> Pattern {
+        let mut p = self.pattern;
+
+        for i in 0..p.1 {
+            let prob = self.probability[i];
+
+            if p.0[i] == 0 || prob >= 100 {
+                continue;
+            }
+
+            if rnd.next_range(0..100) >= prob as u32 {
+                p.0[i] = 0;
+            }
+        }
+
+        p
+    }
+}
+
+/// Velocity of a step, following the convention of the `-`, `x` and `X` strings.
+///
+/// * `0` is off (`-`)
+/// * `1..=127` is a normal hit (`x`)
+/// * `128..=255` is an accent (`X`)
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Velocity(u8);
+
+impl Velocity {
+    /// No hit. Raw value 0.
+    pub const fn off() -> Self {
+        Velocity(0)
+    }
+
+    /// Normal hit. Raw value 127.
+    pub const fn normal() -> Self {
+        Velocity(127)
+    }
+
+    /// Accented hit. Raw value 255.
+    pub const fn accent() -> Self {
+        Velocity(255)
+    }
+
+    pub const fn from_u8(v: u8) -> Self {
+        Velocity(v)
+    }
+
+    /// The raw velocity.
+    pub const fn to_u8(self) -> u8 {
+        self.0
+    }
+
+    /// Whether this is a hit, normal or accent.
+    pub const fn is_on(self) -> bool {
+        self.0 > 0
+    }
+
+    /// Whether this is an accented hit.
+    pub const fn is_accent(self) -> bool {
+        self.0 > 127
+    }
+}
+
+impl From<u8> for Velocity {
+    fn from(v: u8) -> Self {
+        Velocity(v)
+    }
+}
+
+impl From<Velocity> for u8 {
+    fn from(v: Velocity) -> Self {
+        v.0
+    }
+}
+
+/// How to combine the velocities where both patterns have a hit in [`Pat::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The highest velocity of the two.
+    Max,
+    /// The sum of the velocities, saturating at 255.
+    Add,
+    /// The velocity of the other pattern.
+    Overwrite,
+    /// The velocity of this pattern.
+    KeepSelf,
+}
+
+/// A pattern where each hit can retrigger multiple times within its step, also
+/// known as "ratchets".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratchet {
+    /// The pattern of hits.
+    pub pattern: Pattern,
+    /// Number of evenly spaced triggers per step. 0 and 1 are both a normal hit.
+    pub counts: [u8; MAX_LEN],
+}
+
+impl Ratchet {
+    /// Construct a new ratchet pattern where every hit is a normal single trigger.
+    pub fn new(pattern: Pattern) -> Self {
+        Ratchet {
+            pattern,
+            counts: [1; MAX_LEN],
+        }
+    }
+
+    /// Expand into triggers `(time, velocity)` for each step being `step` long. The
+    /// time is counted from the start of the pattern.
+    pub fn events<const FQ: u32>(&self, step: Time<FQ>) -> impl Iterator<Item = (Time<FQ>, u8)> {
+        let r = *self;
+
+        (0..r.pattern.1)
+            .filter(move |i| r.pattern.0[*i] > 0)
+            .flat_map(move |i| {
+                let count = r.counts[i].max(1) as i64;
+                let start = step.count * i as i64;
+                let vel = r.pattern.0[i];
+
+                (0..count).map(move |n| (Time::new(start + (step.count * n) / count), vel))
+            })
+    }
 }
 
 impl PartialEq<&str> for Pat<u8> {
@@ -233,19 +843,57 @@ impl PartialEq<&str> for Pat<u8> {
 
 impl core::fmt::Debug for Pattern {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // {:#?} shows the exact velocities.
+        if f.alternate() {
+            for i in 0..self.1 {
+                if i > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{:02x}", self.0[i])?;
+            }
+            return Ok(());
+        }
+
+        write!(f, "|")?;
+        for i in 0..self.1 {
+            let v = Velocity(self.0[i]);
+            write!(
+                f,
+                "{}",
+                if !v.is_on() {
+                    '-'
+                } else if !v.is_accent() {
+                    'x'
+                } else {
+                    'X'
+                }
+            )?;
+        }
         write!(f, "|")?;
+        Ok(())
+    }
+}
+
+/// Pattern for user facing output, showing velocity in tiers.
+///
+/// * `-` is off
+/// * `.` is velocity 1-63
+/// * `x` is velocity 64-127
+/// * `X` is velocity 128-255
+impl core::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         for i in 0..self.1 {
             write!(
                 f,
                 "{}",
                 match self.0[i] {
                     0 => '-',
-                    x if x <= 127 => 'x',
+                    1..=63 => '.',
+                    64..=127 => 'x',
                     _ => 'X',
                 }
             )?;
         }
-        write!(f, "|")?;
         Ok(())
     }
 }
@@ -280,14 +928,14 @@ impl From<&str> for Pattern {
 
         for step in trimmed.chars() {
             let val = if step == '-' {
-                0
+                Velocity::off()
             } else if step.is_lowercase() {
-                127
+                Velocity::normal()
             } else {
-                255
+                Velocity::accent()
             };
 
-            p.push(val);
+            p.push(val.to_u8());
         }
 
         p
@@ -310,6 +958,375 @@ pub fn trim_pattern(pattern: &str) -> &str {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::euclid::euclid;
+
+    #[test]
+    fn velocity() {
+        assert_eq!(Velocity::off().to_u8(), 0);
+        assert_eq!(Velocity::normal().to_u8(), 127);
+        assert_eq!(Velocity::accent().to_u8(), 255);
+
+        for v in 0..=255 {
+            assert_eq!(u8::from(Velocity::from_u8(v)), v);
+            assert_eq!(Velocity::from(v).is_on(), v > 0);
+            assert_eq!(Velocity::from(v).is_accent(), v > 127);
+        }
+
+        let p: Pattern = "-xX".into();
+        assert_eq!(p.velocity_at(0), Velocity::off());
+        assert_eq!(p.velocity_at(1), Velocity::normal());
+        assert_eq!(p.velocity_at(2), Velocity::accent());
+        assert_eq!(p.velocity_at(3), Velocity::off());
+    }
+
+    #[test]
+    fn from_iter() {
+        let p: Pattern = (0..8).map(|i| i * 16).collect();
+
+        assert_eq!(p.len(), 8);
+        assert_eq!(format!("{:#?}", p), "00 10 20 30 40 50 60 70");
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_iter_overflow() {
+        let _: Pattern = (0..65).collect();
+    }
+
+    #[test]
+    fn merge() {
+        let a = Pattern::from_bits(0b0101, 4, 100);
+        let b = Pattern::from_bits(0b0011, 4, 200);
+
+        let merged = |policy| {
+            let p = a.merge(&b, policy);
+            [p[0], p[1], p[2], p[3]]
+        };
+
+        assert_eq!(merged(MergePolicy::Max), [200, 200, 100, 0]);
+        assert_eq!(merged(MergePolicy::Add), [255, 200, 100, 0]);
+        assert_eq!(merged(MergePolicy::Overwrite), [200, 200, 100, 0]);
+        assert_eq!(merged(MergePolicy::KeepSelf), [100, 200, 100, 0]);
+
+        // Other with a lower velocity.
+        let c = Pattern::from_bits(0b0001, 4, 50);
+        assert_eq!(a.merge(&c, MergePolicy::Max)[0], 100);
+        assert_eq!(a.merge(&c, MergePolicy::Add)[0], 150);
+        assert_eq!(a.merge(&c, MergePolicy::Overwrite)[0], 50);
+        assert_eq!(a.merge(&c, MergePolicy::KeepSelf)[0], 100);
+    }
+
+    #[test]
+    fn offset_mut() {
+        let p: Pattern = "X--x-x--x-".into();
+
+        for n in [0, 1, 3, 10, 17] {
+            let mut q = p;
+            q.offset_mut(n);
+
+            // Offset by splitting and joining.
+            let m = 10 - n as usize % 10;
+            assert_eq!(q, p.sub(m..10) + p.sub(0..m));
+        }
+
+        let mut e = Pattern::new();
+        e.offset_mut(3);
+        assert_eq!(e, Pattern::new());
+
+        // Values past the length don't linger after the offset.
+        let p: Pattern = "x--x".into();
+        assert_eq!(p.offset(1), Pattern::from("xx--"));
+        assert_eq!(Pattern::new_with(127, 4).offset(1), Pattern::from("xxxx"));
+    }
+
+    #[test]
+    fn reverse_mut() {
+        let mut p: Pattern = "X--x-x--".into();
+        p.reverse_mut();
+        assert_eq!(p, "--x-x--X");
+        assert_eq!(p.reverse(), "X--x-x--");
+    }
+
+    #[test]
+    fn ramp_velocity() {
+        let p = euclid(4, 16).ramp_velocity(40, 240);
+
+        assert!(p.eq_rhythm(&euclid(4, 16)));
+
+        let hits: Vec<_> = (0..16).map(|i| p[i]).filter(|v| *v > 0).collect();
+        assert_eq!(hits.first(), Some(&40));
+        assert_eq!(hits.last(), Some(&240));
+        assert!(hits.windows(2).all(|w| w[0] < w[1]), "{:?}", hits);
+
+        // Silence stays silent.
+        let silent: Pattern = "----".into();
+        assert_eq!(silent.ramp_velocity(1, 255), silent);
+    }
+
+    #[test]
+    fn normalize_rotation() {
+        let e = euclid(3, 8);
+
+        assert_eq!(e.normalize_rotation(), e);
+        assert_eq!(e.offset(1).normalize_rotation(), e);
+
+        // Depending on which hit ends up first, we get one of the rotations starting on a hit.
+        let starts: Vec<_> = (0..8)
+            .filter(|i| e[*i] > 0)
+            .map(|i| e.offset(8 - i as u8))
+            .collect();
+
+        for k in 0..8 {
+            let n = e.offset(k).normalize_rotation();
+            assert!(n[0] > 0);
+            assert!(n.eq_rhythm(&n.normalize_rotation()));
+            assert!(starts.iter().any(|s| s.eq_rhythm(&n)), "{:?}", n);
+        }
+
+        let silent: Pattern = "----".into();
+        assert_eq!(silent.normalize_rotation(), silent);
+    }
+
+    #[test]
+    fn xor() {
+        let a: Pattern = "x---X---".into();
+        let b: Pattern = "X-x-".into();
+
+        assert_eq!(a.xor(&b), "--x---x-");
+        assert!(a.xor(&a).is_silent());
+    }
+
+    #[test]
+    fn or() {
+        let a: Pattern = "x---X---".into();
+        let b: Pattern = "X-x-".into();
+
+        assert_eq!(a.or(&b), "X-x-X-x-");
+    }
+
+    #[test]
+    fn rotate_fraction() {
+        let p: Pattern = "X--x-x--x---x-xx".into();
+
+        assert_eq!(p.rotate_fraction(1, 2), p.offset(8));
+        assert_eq!(p.rotate_fraction(1, 4), p.offset(4));
+        assert_eq!(p.rotate_fraction(5, 4), p.offset(4));
+
+        // 16 * 1 / 3 = 5.33
+        assert_eq!(p.rotate_fraction(1, 3), p.offset(5));
+    }
+
+    #[test]
+    fn try_repeat_to() {
+        let p: Pattern = "x-X".into();
+
+        assert_eq!(p.try_repeat_to(100), None);
+        assert_eq!(p.try_repeat_to(32), Some(p.repeat_to(32)));
+    }
+
+    #[test]
+    fn debug_alternate() {
+        let mut p = Pattern::new();
+        p.push(0);
+        p.push(70);
+        p.push(255);
+
+        assert_eq!(format!("{:#?}", p), "00 46 ff");
+        assert_eq!(format!("{:?}", p), "|-xX|");
+    }
+
+    #[test]
+    fn empty_silent() {
+        let p = Pattern::new();
+        assert!(p.is_empty());
+        assert!(!p.is_silent());
+
+        let p: Pattern = "--------".into();
+        assert!(!p.is_empty());
+        assert!(p.is_silent());
+
+        let p: Pattern = "---x----".into();
+        assert!(!p.is_empty());
+        assert!(!p.is_silent());
+    }
+
+    #[test]
+    fn ratchet_events() {
+        let mut r = Ratchet::new("X-x-".into());
+        r.counts[0] = 4;
+
+        let step = Time::<1000>::new(1000);
+        let events: Vec<_> = r.events(step).map(|(t, v)| (t.count(), v)).collect();
+
+        assert_eq!(
+            events,
+            [(0, 255), (250, 255), (500, 255), (750, 255), (2000, 127)]
+        );
+    }
+
+    #[test]
+    fn split_at() {
+        let p = euclid(4, 16);
+
+        let (a, b) = p.split_at(8);
+        assert_eq!(a, "|x---x---|");
+        assert_eq!(b, "|x---x---|");
+        assert_eq!(a + b, p);
+
+        let (a, b) = p.split_at(100);
+        assert_eq!(a, p);
+        assert_eq!(b.len(), 0);
+    }
+
+    #[test]
+    fn next_hit() {
+        let p = euclid(4, 16);
+
+        assert_eq!(p.next_hit(0), Some(0));
+        assert_eq!(p.next_hit(1), Some(4));
+        assert_eq!(p.next_hit(5), Some(8));
+        assert_eq!(p.next_hit(13), None);
+        assert_eq!(p.next_hit(100), None);
+
+        assert_eq!(p.next_hit_wrapping(5), Some(8));
+        assert_eq!(p.next_hit_wrapping(13), Some(0));
+        assert_eq!(p.next_hit_wrapping(100), Some(0));
+
+        let silent = Pattern::new_with(0, 16);
+        assert_eq!(silent.next_hit(0), None);
+        assert_eq!(silent.next_hit_wrapping(3), None);
+    }
+
+    #[test]
+    fn scale_velocity() {
+        let mut p = Pattern::new_with(200, 4);
+        p[1] = 0;
+
+        let s = p.scale_velocity(0.5);
+        assert_eq!(s[0], 100);
+        assert_eq!(s[1], 0);
+
+        assert_eq!(p.scale_velocity(1000.0)[0], 255);
+        assert_eq!(p.scale_velocity(0.0)[0], 1);
+        assert_eq!(p.scale_velocity(1.0)[0], 200);
+    }
+
+    #[test]
+    fn accent() {
+        let p = euclid(8, 16).accent(&euclid(2, 16), 255);
+
+        assert_eq!(p, "|X-x-x-x-X-x-x-x-|");
+        assert_eq!(p.count(0..16), 8);
+
+        // Template is repeated.
+        let p = euclid(8, 16).accent(&"x---".into(), 255);
+        assert_eq!(p, "|X-x-X-x-X-x-X-x-|");
+    }
+
+    #[test]
+    fn chunks() {
+        let p = euclid(5, 16);
+
+        let chunks: Vec<_> = p.chunks(4).collect();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|c| c.len() == 4));
+
+        let joined = chunks.into_iter().fold(Pattern::new(), |a, b| a + b);
+        assert_eq!(joined, p);
+
+        let chunks: Vec<_> = p.chunks(6).collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].len(), 4);
+
+        assert_eq!(p.chunks(0).count(), 0);
+    }
+
+    #[test]
+    fn blend() {
+        let p1 = Pattern::new_with(0, 16);
+        let p2 = Pattern::new_with(255, 16);
+
+        let b = p1.blend(&p2, 0.5);
+        assert_eq!(b.len(), 16);
+        for i in 0..16 {
+            assert!((127..=128).contains(&b[i]));
+        }
+
+        let b = p1.blend(&p2, 0.0);
+        assert!((0..16).all(|i| b[i] == 0));
+        let b = p1.blend(&p2, 1.0);
+        assert!((0..16).all(|i| b[i] == 255));
+
+        // Shorter is repeated.
+        let p3: Pattern = "x-".into();
+        assert_eq!(p3.blend(&p2, 0.0), "x-x-x-x-x-x-x-x-");
+    }
+
+    #[test]
+    fn eq_rhythm() {
+        let p1: Pattern = "xX--".into();
+        let p2: Pattern = "xx--".into();
+
+        assert!(p1.eq_rhythm(&p2));
+        assert_ne!(p1, p2);
+
+        assert!(!p1.eq_rhythm(&"xx-x".into()));
+        assert!(!p1.eq_rhythm(&"xX---".into()));
+    }
+
+    #[test]
+    fn fill_to() {
+        let mut p = euclid(2, 16);
+        p[8] = 100;
+
+        let f = p.fill_to(4);
+        assert_eq!(f.to_bits(), euclid(4, 16).to_bits());
+        assert_eq!(f[8], 100);
+
+        assert_eq!(euclid(4, 16).fill_to(2), euclid(2, 16));
+        assert_eq!(p.fill_to(0), "|----------------|");
+        assert_eq!(p.fill_to(100).count(0..16), 16);
+    }
+
+    #[test]
+    fn prob_pattern_resolve() {
+        let mut p = ProbPattern::new("xxxx".into());
+        p.probability[1] = 0;
+        p.probability[2] = 50;
+
+        let mut rnd = Rnd::new(42);
+        let mut fired = 0;
+
+        for _ in 0..100 {
+            let r = p.resolve(&mut rnd);
+            assert_eq!(r[0], 127);
+            assert_eq!(r[1], 0);
+            assert_eq!(r[3], 127);
+
+            if r[2] > 0 {
+                fired += 1;
+            }
+        }
+
+        assert!(fired > 25 && fired < 75);
+    }
+
+    #[test]
+    fn set_zero_length() {
+        let mut p = Pat::<u8>::new();
+        p.set(0, 70);
+        p.set(-1, 70);
+        assert_eq!(p.len(), 0);
+    }
+
+    #[test]
+    fn set_wraps() {
+        let mut p = Pattern::new_with(0, 4);
+        p.set(5, 1);
+        p.set(-1, 2);
+        assert_eq!(p[1], 1);
+        assert_eq!(p[3], 2);
+    }
 
     #[test]
     fn pattern_add() {
@@ -360,4 +1377,89 @@ mod test {
         assert_eq!(trim_pattern("|-"), "-");
         assert_eq!(trim_pattern("-"), "-");
     }
+
+    #[test]
+    fn pattern_humanize() {
+        let p: Pattern = "X-x-XXxx".into();
+
+        let h1 = p.humanize(&mut Rnd::new(42), 30, 64);
+        let h2 = p.humanize(&mut Rnd::new(42), 30, 64);
+        assert_eq!(h1, h2);
+
+        assert!((0..8).any(|i| h1.velocity_delta(i) != 0));
+
+        for i in 0..8 {
+            if p[i] == 0 {
+                assert_eq!(h1.velocity(i), 0);
+                assert_eq!(h1.timing(i), 0.0);
+            } else {
+                assert!(h1.velocity(i) > 0);
+                assert!(h1.timing(i).abs() <= 0.25);
+            }
+        }
+
+        // Stays within bounds.
+        let p: Pattern = "XXXXXXXX".into();
+        let h = p.humanize(&mut Rnd::new(42), 255, 0);
+        for i in 0..8 {
+            assert!(h.velocity_delta(i) <= 0);
+            assert!(h.velocity(i) > 0);
+        }
+    }
+
+    #[test]
+    fn pattern_resample() {
+        let p = euclid(4, 16);
+
+        assert_eq!(p.resample(8), "x-x-x-x-");
+        assert_eq!(p.resample(32), "xx------xx------xx------xx------");
+        assert_eq!(p.resample(16), p);
+        assert_eq!(p.resample(0).len(), 0);
+        assert_eq!(Pattern::new().resample(8).len(), 0);
+
+        let p: Pattern = "xX-x".into();
+        assert_eq!(p.resample(8), "xxXX--xx");
+    }
+
+    #[test]
+    fn pattern_count() {
+        let p = euclid(4, 16);
+
+        assert_eq!(p.count(0..16), 4);
+        assert_eq!(p.count(0..5), 2);
+        assert_eq!(p.count(1..8), 1);
+        assert_eq!(p.count(8..100), 2);
+        assert_eq!(p.count(5..5), 0);
+        assert_eq!(p.count(Range { start: 9, end: 3 }), 0);
+    }
+
+    #[test]
+    fn pattern_display() {
+        let mut p = Pattern::new();
+        for v in [0, 1, 63, 64, 127, 128, 255, 0] {
+            p.push(v);
+        }
+
+        assert_eq!(format!("{}", p), "-..xxXX-");
+        assert_eq!(format!("{:?}", p), "|-xxxxXX-|");
+    }
+
+    #[test]
+    fn pattern_bits() {
+        let p = euclid(5, 16);
+
+        let bits = p.to_bits();
+        assert_eq!(bits, 0b0010_0100_1001_0001);
+        assert_eq!(Pattern::from_bits(bits, 16, 127), p);
+
+        let p = Pattern::from_bits(u64::MAX, 64, 255);
+        assert_eq!(p.len(), 64);
+        assert_eq!(p.to_bits(), u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "Pattern from bits len 65 > 64")]
+    fn pattern_bits_too_long() {
+        Pattern::from_bits(0, 65, 127);
+    }
 }
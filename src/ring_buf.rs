@@ -13,6 +13,16 @@ impl<T: Copy, const X: usize> RingBuf<T, X> {
         }
     }
 
+    /// Create a buffer holding the elements of the slice. The capacity is `X - 1`,
+    /// and a slice longer than that gives `None`.
+    pub fn from_slice(slice: &[T]) -> Option<Self> {
+        if slice.len() >= X {
+            return None;
+        }
+
+        Some(slice.iter().copied().collect())
+    }
+
     #[inline(always)]
     pub fn len(&self) -> usize {
         if self.remove <= self.insert {
@@ -39,4 +49,85 @@ impl<T: Copy, const X: usize> RingBuf<T, X> {
         }
         x
     }
+
+    /// Pop all elements in order. The buffer is empty afterwards, also if the
+    /// iterator is dropped before the end.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        Drain(self)
+    }
+}
+
+struct Drain<'a, T: Copy, const X: usize>(&'a mut RingBuf<T, X>);
+
+impl<'a, T: Copy, const X: usize> Iterator for Drain<'a, T, X> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+}
+
+impl<'a, T: Copy, const X: usize> Drop for Drain<'a, T, X> {
+    fn drop(&mut self) {
+        while self.0.pop().is_some() {}
+    }
+}
+
+/// Panics if the iterator has more than `X - 1` elements.
+impl<T: Copy, const X: usize> FromIterator<T> for RingBuf<T, X> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buf = RingBuf::new();
+
+        for el in iter {
+            buf.push(el);
+        }
+
+        buf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_slice() {
+        let mut buf = RingBuf::<u8, 8>::from_slice(&[1, 2, 3, 4, 5]).unwrap();
+
+        assert_eq!(buf.len(), 5);
+        for i in 1..=5 {
+            assert_eq!(buf.pop(), Some(i));
+        }
+        assert_eq!(buf.pop(), None);
+
+        // Still usable after wrapping around.
+        buf.push(6);
+        assert_eq!(buf.pop(), Some(6));
+
+        assert!(RingBuf::<u8, 4>::from_slice(&[1, 2, 3, 4]).is_none());
+    }
+
+    #[test]
+    fn drain() {
+        let mut buf: RingBuf<u8, 8> = (1..=5).collect();
+
+        assert_eq!(buf.drain().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(buf.len(), 0);
+        assert_eq!(buf.pop(), None);
+
+        buf.push(6);
+        buf.push(7);
+        assert_eq!(buf.drain().next(), Some(6));
+        assert_eq!(buf.len(), 0);
+    }
+
+    #[test]
+    fn from_iter() {
+        let mut buf: RingBuf<u8, 8> = (1..=3).collect();
+
+        assert_eq!(buf.pop(), Some(1));
+        assert_eq!(buf.pop(), Some(2));
+        assert_eq!(buf.pop(), Some(3));
+        assert_eq!(buf.pop(), None);
+    }
 }
@@ -1,4 +1,5 @@
 use core::num::Wrapping as w;
+use core::ops::Range;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Rnd(u32);
@@ -11,6 +12,17 @@ impl Rnd {
         Rnd(seed)
     }
 
+    /// Restore a generator from a `state()`. This is the same as `new()`.
+    pub fn from_state(state: u32) -> Self {
+        Rnd(state)
+    }
+
+    /// The current position in the random stream. Use with `from_state()` to
+    /// continue the exact same sequence later.
+    pub fn state(&self) -> u32 {
+        self.0
+    }
+
     pub fn next(&mut self) -> u32 {
         let mut z = w(self.0) + w(0x6D2B79F5);
         self.0 = z.0;
@@ -18,6 +30,80 @@ impl Rnd {
         z ^= z + (z ^ (z >> 7)) * (z | w(61));
         (z ^ (z >> 14)).0
     }
+
+    /// Next random number in the (half open) range. Returns `range.start` for an empty range.
+    pub fn next_range(&mut self, range: Range<u32>) -> u32 {
+        let n = self.next();
+
+        if range.end <= range.start {
+            return range.start;
+        }
+
+        range.start + n % (range.end - range.start)
+    }
+
+    /// Next random number as a float in the range 0.0..1.0.
+    pub fn next_f32(&mut self) -> f32 {
+        // 24 bits is what fits in the f32 mantissa.
+        (self.next() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Shuffle the items in place (Fisher–Yates). The same seed gives the same order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_range(0..i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// xoshiro256++ PRNG with a 2^256 - 1 period.
+///
+/// Slower and bigger than [`Rnd`], but suitable as an audio rate noise source
+/// where a short period would be heard as a repeating texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rnd64([u64; 4]);
+
+impl Rnd64 {
+    pub fn new(seed: u32) -> Self {
+        // Expand the seed using splitmix64, which never yields an all zero state.
+        let mut x = seed as u64;
+        let mut s = [0; 4];
+
+        for v in &mut s {
+            x = x.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *v = z ^ (z >> 31);
+        }
+
+        Rnd64(s)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> u32 {
+        // The upper bits are the better ones.
+        (self.next_u64() >> 32) as u32
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.0;
+
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
 }
 
 #[cfg(test)]
@@ -31,4 +117,63 @@ mod test {
         assert_eq!(r.next(), 324989476);
         assert_eq!(r.next(), 2491772807);
     }
+
+    #[test]
+    fn test_seq_64() {
+        let mut r = Rnd64::new(12);
+        assert_eq!(r.next(), 2480233593);
+        assert_eq!(r.next(), 3784918586);
+        assert_eq!(r.next_u64(), 12441750968137966484);
+    }
+
+    #[test]
+    fn test_next_range() {
+        let mut r = Rnd::new(12);
+        for _ in 0..1000 {
+            let v = r.next_range(3..7);
+            assert!((3..7).contains(&v));
+        }
+        assert_eq!(r.next_range(5..5), 5);
+    }
+
+    #[test]
+    fn test_next_f32() {
+        let mut r = Rnd::new(12);
+        for _ in 0..1000 {
+            let v = r.next_f32();
+            assert!((0.0..1.0).contains(&v));
+        }
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut items = [0, 1, 2, 3, 4];
+        Rnd::new(12).shuffle(&mut items);
+
+        let mut again = [0, 1, 2, 3, 4];
+        Rnd::new(12).shuffle(&mut again);
+        assert_eq!(items, again);
+
+        let mut sorted = items;
+        sorted.sort();
+        assert_eq!(sorted, [0, 1, 2, 3, 4]);
+
+        // Pin the order.
+        assert_eq!(items, [3, 1, 2, 4, 0]);
+    }
+
+    #[test]
+    fn test_state() {
+        let mut r1 = Rnd::new(12);
+        r1.next();
+
+        let mut r2 = r1;
+        let mut r3 = Rnd::from_state(r1.state());
+
+        for _ in 0..10 {
+            let n = r1.next();
+            assert_eq!(r2.next(), n);
+            assert_eq!(r3.next(), n);
+        }
+    }
 }
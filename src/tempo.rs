@@ -33,6 +33,17 @@ impl<const CLK: u32> Tempo<CLK> {
         }
     }
 
+    /// Forget all previous intervals and predictions.
+    ///
+    /// Unlike `reset()`, this doesn't try to keep the even/odd swing. The next
+    /// `predict()` proceeds as if the tempo was newly constructed. Useful on an explicit
+    /// stop/start, where the tempo can be very different after the restart.
+    pub fn hard_reset(&mut self) {
+        self.intervals = [None; 6];
+        self.next = 0;
+        self.predicted = 0;
+    }
+
     /// Update with a new interval, and get back the predicted next interval.
     pub fn predict(&mut self, interval: Time<CLK>) -> Time<CLK> {
         if self.next >= self.intervals.len() {
@@ -85,6 +96,37 @@ impl<const CLK: u32> Tempo<CLK> {
         }
     }
 
+    /// Update with a new interval measured at `now`, and get back the predicted time
+    /// of the next beat. This is `now + predict(interval)`.
+    pub fn predict_next_time(&mut self, interval: Time<CLK>, now: Time<CLK>) -> Time<CLK> {
+        now + self.predict(interval)
+    }
+
+    /// Ratio of the average odd interval to the average even interval. 1.0 is straight
+    /// and 1.5 (or 0.67) is triplet swing. `None` until enough intervals are measured.
+    ///
+    /// Which of even and odd is the onbeat is not known.
+    pub fn swing_ratio(&self) -> Option<f32> {
+        if self.intervals.iter().any(|i| i.is_none()) {
+            return None;
+        }
+
+        let average = |next| {
+            let series = IntervalIterator { tempo: self, next };
+            let (count, sum) = series.fold((0, 0), |(c, s), y| (c + 1, s + y));
+            sum as f32 / count as f32
+        };
+
+        let even = average(0);
+        let odd = average(1);
+
+        if even == 0.0 {
+            return None;
+        }
+
+        Some(odd / even)
+    }
+
     fn series<'a>(&'a self) -> IntervalIterator<'a, CLK> {
         IntervalIterator {
             tempo: &self,
@@ -151,6 +193,17 @@ impl<'a, const CLK: u32> Iterator for IntervalIterator<'a, CLK> {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_predict_next_time() {
+        let mut t = Tempo::<1000>::new();
+
+        for i in 1..20 {
+            let now = Time::from_secs(i);
+            let next = t.predict_next_time(Time::from_secs(1), now);
+            assert_eq!(next, now + Time::from_secs(1));
+        }
+    }
+
     #[test]
     fn test_predict_straight() {
         let mut t = Tempo::<1000>::new();
@@ -199,4 +252,39 @@ mod test {
         assert_eq!(t.predict(Time::from_secs(3)).count(), 2000);
         assert_eq!(t.predict(Time::from_secs(2)).count(), 3000);
     }
+
+    #[test]
+    fn test_hard_reset() {
+        let mut t = Tempo::<1000>::new();
+
+        for _ in 0..5 {
+            t.predict(Time::from_secs(1));
+        }
+
+        t.hard_reset();
+        assert_eq!(t, Tempo::new());
+
+        assert_eq!(t.predict(Time::from_secs(5)).count(), 5000);
+    }
+
+    #[test]
+    fn test_swing_ratio() {
+        let mut t = Tempo::<1000>::new();
+
+        assert_eq!(t.swing_ratio(), None);
+
+        for _ in 0..3 {
+            t.predict(Time::from_secs(3));
+            t.predict(Time::from_secs(2));
+        }
+
+        let r = t.swing_ratio().unwrap();
+        assert!((r - 2.0 / 3.0).abs() < 0.01, "{}", r);
+
+        let mut t = Tempo::<1000>::new();
+        for _ in 0..6 {
+            t.predict(Time::from_secs(1));
+        }
+        assert_eq!(t.swing_ratio(), Some(1.0));
+    }
 }
@@ -1,4 +1,10 @@
+#[allow(unused_imports)]
+use micromath::F32Ext;
+
+use core::cell::Cell;
+
 use crate::clock::Time;
+use crate::rnd::Rnd;
 
 pub struct WaveTableBuffer<W1: WaveTable, W2: WaveTable, const LEN: usize, const FQ: u32> {
     /// One wavetable.
@@ -24,6 +30,45 @@ pub struct WaveTableBuffer<W1: WaveTable, W2: WaveTable, const LEN: usize, const
 
     /// Next set of parameters when doing advance_buffer().
     params_next: Option<WaveTableParams>,
+
+    /// Time in seconds to glide to a new frequency. 0.0 to change instantly.
+    glide_secs: f32,
+
+    /// Current frequency, which differs from `params.freq` while gliding.
+    freq: f32,
+
+    /// Frequency the current glide is heading for.
+    glide_target: f32,
+
+    /// Frequency change per sample for the current glide.
+    glide_step: f32,
+
+    /// How the second wavetable is combined with the first.
+    combine: CombineMode,
+
+    /// Phase offset of the right buffer, where 1.0 is a full oscillation. None for mono.
+    stereo_phase: Option<f32>,
+
+    /// Current offset in wt1 for the right buffer.
+    acc1_right: Accumulator,
+
+    /// Current offset in wt2 for the right buffer.
+    acc2_right: Accumulator,
+
+    /// Buffered right output when `stereo_phase` is set.
+    buffer_right: [f32; LEN],
+}
+
+/// How the second wavetable is applied to the first, `offset` being the amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CombineMode {
+    /// Crossfade between the first and the second.
+    #[default]
+    Blend,
+    /// Crossfade between the first and the first multiplied by the second.
+    Ring,
+    /// Add the second on top of the first.
+    Add,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -35,6 +80,23 @@ pub struct WaveTableParams {
     pub freq: f32,
 }
 
+impl WaveTableParams {
+    /// Create parameters from a MIDI note number, where note 69 is A4 (440Hz).
+    pub fn from_note(note: u8, offset: f32) -> Self {
+        let semitones = note as f32 - 69.0;
+
+        WaveTableParams {
+            offset,
+            freq: 440.0 * 2.0_f32.powf(semitones / 12.0),
+        }
+    }
+
+    /// Detune the frequency by a number of cents (1/100 of a semitone).
+    pub fn detune_cents(&mut self, cents: f32) {
+        self.freq *= 2.0_f32.powf(cents / 1200.0);
+    }
+}
+
 impl<W1: WaveTable, W2: WaveTable, const LEN: usize, const FQ: u32>
     WaveTableBuffer<W1, W2, LEN, FQ>
 {
@@ -56,6 +118,15 @@ impl<W1: WaveTable, W2: WaveTable, const LEN: usize, const FQ: u32>
             buffer_morph,
             params: params,
             params_next: None,
+            glide_secs: 0.0,
+            freq: params.freq,
+            glide_target: params.freq,
+            glide_step: 0.0,
+            combine: CombineMode::Blend,
+            stereo_phase: None,
+            acc1_right: Accumulator(0.0),
+            acc2_right: Accumulator(0.0),
+            buffer_right: [0_f32; LEN],
         }
     }
 
@@ -63,6 +134,31 @@ impl<W1: WaveTable, W2: WaveTable, const LEN: usize, const FQ: u32>
         &self.buffer
     }
 
+    /// The right buffer when a stereo phase is set, otherwise the same as `buffer()`.
+    pub fn buffer_right(&self) -> &[f32] {
+        if self.stereo_phase.is_some() {
+            &self.buffer_right
+        } else {
+            &self.buffer
+        }
+    }
+
+    /// Phase offset of the right buffer. None when mono.
+    pub fn stereo_phase(&self) -> Option<f32> {
+        self.stereo_phase
+    }
+
+    /// Make the right buffer run the same oscillator offset by `phase`, where 1.0 is a
+    /// full oscillation (0.25 is 90°). None turns it off.
+    pub fn set_stereo_phase(&mut self, phase: Option<f32>) {
+        self.stereo_phase = phase;
+
+        if let Some(phase) = phase {
+            self.acc1_right = self.wt1.shift_phase(self.acc1, phase);
+            self.acc2_right = self.wt2.shift_phase(self.acc2, phase);
+        }
+    }
+
     pub fn params(&self) -> &WaveTableParams {
         &self.params
     }
@@ -71,44 +167,170 @@ impl<W1: WaveTable, W2: WaveTable, const LEN: usize, const FQ: u32>
         self.params_next = Some(params);
     }
 
-    pub fn advance_time(&mut self) {
+    /// Time in seconds to glide to a new frequency.
+    pub fn glide_secs(&self) -> f32 {
+        self.glide_secs
+    }
+
+    /// Set the time in seconds to glide to a new frequency. The frequency changes
+    /// linearly per sample over this time. 0.0 changes the frequency instantly.
+    pub fn set_glide_secs(&mut self, glide_secs: f32) {
+        self.glide_secs = glide_secs;
+    }
+
+    /// How the second wavetable is combined with the first.
+    pub fn combine(&self) -> CombineMode {
+        self.combine
+    }
+
+    /// Set how the second wavetable is combined with the first.
+    pub fn set_combine(&mut self, combine: CombineMode) {
+        self.combine = combine;
+    }
+
+    /// Current frequency. While gliding, this is somewhere between the previous
+    /// and the next frequency.
+    pub fn freq(&self) -> f32 {
+        self.freq
+    }
+
+    /// Advance one sample and get the value. This is the same as the values of the
+    /// `buffer()` after `advance_time()`, except that new parameters from `set_params()`
+    /// apply directly without morphing.
+    pub fn sample(&mut self) -> f32 {
+        if let Some(next) = self.params_next.take() {
+            self.params = next;
+        }
+
         let dt: Time<FQ> = Time::new(1);
+        let freq = self.next_freq(self.params.freq);
+        let (offset, combine) = (self.params.offset, self.combine);
 
-        // Prefer next parameter frequency
-        let freq = self.params_next.unwrap_or(self.params).freq;
-
-        let (acc1, acc2) = fill_buf(
-            &self.wt1,
-            &self.wt2,
-            self.acc1,
-            self.acc2,
-            dt,
-            freq,
-            &mut self.buffer,
-            self.params.offset,
+        let mut value = [0.0];
+
+        (self.acc1, self.acc2) = fill_buf::<_, _, FQ>(
+            &self.wt1, &self.wt2, self.acc1, self.acc2, dt, freq, &mut value, offset, combine,
         );
 
-        if let Some(next) = self.params_next {
-            let _ = fill_buf(
+        if self.stereo_phase.is_some() {
+            // Keep the right side in phase.
+            let mut right = [0.0];
+
+            (self.acc1_right, self.acc2_right) = fill_buf::<_, _, FQ>(
                 &self.wt1,
                 &self.wt2,
-                self.acc1,
-                self.acc2,
+                self.acc1_right,
+                self.acc2_right,
                 dt,
                 freq,
-                &mut self.buffer_morph,
-                next.offset,
+                &mut right,
+                offset,
+                combine,
             );
+        }
+
+        value[0]
+    }
+
+    /// Move the frequency one sample towards `target`, gliding if configured.
+    fn next_freq(&mut self, target: f32) -> f32 {
+        if self.glide_secs <= 0.0 || self.freq == target {
+            self.freq = target;
+            self.glide_target = target;
+            return target;
+        }
+
+        if self.glide_target != target {
+            // New glide from wherever we are now.
+            self.glide_target = target;
+            self.glide_step = (target - self.freq) / (self.glide_secs * FQ as f32);
+        }
+
+        self.freq += self.glide_step;
+
+        let passed = if self.glide_step > 0.0 {
+            self.freq > target
+        } else {
+            self.freq < target
+        };
+
+        if passed {
+            self.freq = target;
+        }
+
+        self.freq
+    }
+
+    pub fn advance_time(&mut self) {
+        let dt: Time<FQ> = Time::new(1);
+
+        // Prefer next parameter frequency
+        let target = self.params_next.unwrap_or(self.params).freq;
+
+        let gliding = self.glide_secs > 0.0 && self.freq != target;
+
+        // Frequency per sample of the buffer.
+        let mut freqs = [target; LEN];
+
+        if gliding {
+            for f in &mut freqs {
+                *f = self.next_freq(target);
+            }
+        } else {
+            self.next_freq(target);
+        }
 
-            // weight between buffers moving from 0.0..1.0 over LEN
-            let mut w = 0.0;
+        let (wt1, wt2, combine) = (&self.wt1, &self.wt2, self.combine);
+
+        let fill = |mut acc1, mut acc2, buf: &mut [f32; LEN], offset| {
+            if gliding {
+                // Sample by sample, since the frequency changes for each.
+                for (i, freq) in freqs.iter().enumerate() {
+                    (acc1, acc2) = fill_buf::<_, _, FQ>(
+                        wt1,
+                        wt2,
+                        acc1,
+                        acc2,
+                        dt,
+                        *freq,
+                        &mut buf[i..=i],
+                        offset,
+                        combine,
+                    );
+                }
+                (acc1, acc2)
+            } else {
+                fill_buf::<_, _, FQ>(wt1, wt2, acc1, acc2, dt, target, buf, offset, combine)
+            }
+        };
 
-            // delta to move for each step.
-            let dw = 1.0 / LEN as f32;
+        let stereo = self.stereo_phase.is_some();
 
-            for (b1, b2) in self.buffer.iter_mut().zip(self.buffer_morph.iter()) {
-                *b1 = *b1 + (*b2 - *b1) * w;
-                w += dw;
+        let (acc1, acc2) = fill(self.acc1, self.acc2, &mut self.buffer, self.params.offset);
+
+        let right = if stereo {
+            fill(
+                self.acc1_right,
+                self.acc2_right,
+                &mut self.buffer_right,
+                self.params.offset,
+            )
+        } else {
+            (self.acc1_right, self.acc2_right)
+        };
+
+        if let Some(next) = self.params_next {
+            let _ = fill(self.acc1, self.acc2, &mut self.buffer_morph, next.offset);
+            morph(&mut self.buffer, &self.buffer_morph);
+
+            if stereo {
+                let _ = fill(
+                    self.acc1_right,
+                    self.acc2_right,
+                    &mut self.buffer_morph,
+                    next.offset,
+                );
+                morph(&mut self.buffer_right, &self.buffer_morph);
             }
 
             // update current set of parameters.
@@ -118,25 +340,70 @@ impl<W1: WaveTable, W2: WaveTable, const LEN: usize, const FQ: u32>
         // remember accumulator for next advance_time() call
         self.acc1 = acc1;
         self.acc2 = acc2;
+        (self.acc1_right, self.acc2_right) = right;
     }
 }
 
+/// Morph `buf` into `to` with a weight moving from 0.0..1.0 over the length.
+fn morph<const LEN: usize>(buf: &mut [f32; LEN], to: &[f32; LEN]) {
+    // weight between buffers moving from 0.0..1.0 over LEN
+    let mut w = 0.0;
+
+    // delta to move for each step.
+    let dw = 1.0 / LEN as f32;
+
+    for (b1, b2) in buf.iter_mut().zip(to.iter()) {
+        *b1 = *b1 + (*b2 - *b1) * w;
+        w += dw;
+    }
+}
+
+/// Size of the scratch buffer used to combine the wavetables in [`CombineMode::Ring`]
+/// and [`CombineMode::Add`].
+const COMBINE_CHUNK: usize = 16;
+
 #[inline(always)]
+#[allow(clippy::too_many_arguments)]
 fn fill_buf<W1: WaveTable, W2: WaveTable, const FQ: u32>(
     wt1: &W1,
     wt2: &W2,
     acc1: Accumulator,
-    acc2: Accumulator,
+    mut acc2: Accumulator,
     dt: Time<FQ>,
     freq: f32,
     buf: &mut [f32],
     offset: f32,
+    combine: CombineMode,
 ) -> (Accumulator, Accumulator) {
     // First fill buffer with value of wt1, offset 1.0 to definitely get this wave
     let acc1 = wt1.fill_buf(acc1, dt, freq, buf, 1.0);
 
-    // Second add to buffer the offset from wt2
-    let acc2 = wt2.fill_buf(acc2, dt, freq, buf, offset);
+    let ring = match combine {
+        CombineMode::Blend => {
+            // Second add to buffer the offset from wt2
+            let acc2 = wt2.fill_buf(acc2, dt, freq, buf, offset);
+
+            return (acc1, acc2);
+        }
+        CombineMode::Ring => true,
+        CombineMode::Add => false,
+    };
+
+    // The other modes need the values of wt2 on their own, which are made in chunks.
+    let mut other = [0.0; COMBINE_CHUNK];
+
+    for chunk in buf.chunks_mut(COMBINE_CHUNK) {
+        let other = &mut other[..chunk.len()];
+        acc2 = wt2.fill_buf(acc2, dt, freq, other, 1.0);
+
+        for (b, o) in chunk.iter_mut().zip(other.iter()) {
+            *b = if ring {
+                *b + (*b * *o - *b) * offset
+            } else {
+                *b + *o * offset
+            };
+        }
+    }
 
     (acc1, acc2)
 }
@@ -156,6 +423,12 @@ pub trait WaveTable {
         // How much to "replace" the current in the buffer. 0.0 means retain buffer as is. 1.0 fully replace.
         replace: f32,
     ) -> Accumulator;
+
+    /// Move the accumulator `phase` forward, where 1.0 is one oscillation. The default
+    /// is for accumulators holding the fractional phase 0.0..1.0.
+    fn shift_phase(&self, acc: Accumulator, phase: f32) -> Accumulator {
+        Accumulator(((acc.0 + phase) % 1.0 + 1.0) % 1.0)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -199,13 +472,16 @@ impl<const LEN: usize> WaveTable for ArrayWaveTable<LEN> {
                 offset_el -= len;
             }
 
-            // Index into array
-            let n = offset_el as usize;
-
-            // weight between two adjacent elements in the array.
-            let w = offset_el - (n as f32);
+            // Index into array, and weight between two adjacent elements in the array.
+            // Landing exactly on the end means fully weighted to the last element,
+            // since n+1 would be out of bounds.
+            let (n, w) = if offset_el >= len {
+                (LEN - 2, 1.0)
+            } else {
+                let n = offset_el as usize;
+                (n, offset_el - (n as f32))
+            };
 
-            // n+1 is always ok, since offset_el is always (LEN - 1).
             let (el1, el2) = (self.elements[n], self.elements[n + 1]);
 
             // weighted value between elements
@@ -223,10 +499,18 @@ impl<const LEN: usize> WaveTable for ArrayWaveTable<LEN> {
 
         Accumulator(offset_el)
     }
+
+    fn shift_phase(&self, acc: Accumulator, phase: f32) -> Accumulator {
+        // The accumulator is the fractional index.
+        let len = (LEN - 1) as f32;
+        Accumulator((((acc.0 / len) + phase) % 1.0 + 1.0) % 1.0 * len)
+    }
 }
 
 pub enum BasicWavetable {
     Saw,
+    /// Band-limited saw using PolyBLEP to reduce aliasing at the discontinuity.
+    BlSaw,
     Square,
     Sine,
     Triangle,
@@ -242,59 +526,144 @@ impl WaveTable for BasicWavetable {
         buf: &mut [f32],
         replace: f32,
     ) -> Accumulator {
-        // NB. dt.count is typically 1, so "as f32" is fine despite it being an i64
-        let dp = (dt.count as f32 * freq) / FQ as f32;
+        fill_fract(acc, dt, freq, buf, replace, |fract, dp| match self {
+            BasicWavetable::Saw => {
+                //   /|
+                //  / |
+                // -  |
+                //    | /
+                //    |/
+
+                let min_step = 1.0 / FQ as f32;
+
+                // start at 0.0
+                if fract < min_step {
+                    0.0
+                } else if fract <= 0.5 {
+                    fract * 2.0
+                } else {
+                    -1.0 + (fract - 0.5) * 2.0
+                }
+            }
+            BasicWavetable::BlSaw => {
+                // Same shape as Saw, but the ramp measured from the discontinuity,
+                // which is where it drops from 1.0 to -1.0 in the middle.
+                let t = if fract < 0.5 {
+                    fract + 0.5
+                } else {
+                    fract - 0.5
+                };
+
+                let naive = 2.0 * t - 1.0;
+
+                naive - poly_blep(t, dp)
+            }
+            BasicWavetable::Square => {
+                // ---|
+                //    |
+                //    |
+                //    |
+                //    |---
+                if fract <= 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            BasicWavetable::Sine => {
+                let deg = fract * u32::MAX as f32;
+                crate::geom::sin(deg as u32) as f32 / 32768.0
+            }
+            BasicWavetable::Triangle => {
+                let deg = fract * u32::MAX as f32;
+                crate::geom::tri(deg as u32) as f32 / 32768.0
+            }
+        })
+    }
+}
+
+pub struct FnWaveTable<F> {
+    f: F,
+}
+
+impl<F: Fn(f32) -> f32> FnWaveTable<F> {
+    /// Create a wavetable from a function taking the fractional phase 0.0..1.0
+    /// of one oscillation, returning the value -1.0..1.0.
+    pub fn new(f: F) -> Self {
+        FnWaveTable { f }
+    }
+}
+
+impl<F: Fn(f32) -> f32> WaveTable for FnWaveTable<F> {
+    #[inline(always)]
+    fn fill_buf<const FQ: u32>(
+        &self,
+        acc: Accumulator,
+        dt: Time<FQ>,
+        freq: f32,
+        buf: &mut [f32],
+        replace: f32,
+    ) -> Accumulator {
+        fill_fract(acc, dt, freq, buf, replace, |fract, _| (self.f)(fract))
+    }
+}
+
+/// Noise source. Without a `step` this is white noise, otherwise each random value
+/// is held for `step` samples, which gives a "pitched" sample-and-hold noise.
+///
+/// The frequency is ignored. The accumulator holds the number of samples since the
+/// last random value was drawn.
+pub struct NoiseWaveTable {
+    rnd: Cell<Rnd>,
+    /// Number of samples to hold each value. 1 is white noise.
+    step: usize,
+    /// Currently held value.
+    held: Cell<f32>,
+}
+
+impl NoiseWaveTable {
+    /// Create a white noise source.
+    pub fn new(seed: u32) -> Self {
+        Self::with_step(seed, 1)
+    }
+
+    /// Create a sample-and-hold noise source that draws a new value every `step` samples.
+    pub fn with_step(seed: u32, step: usize) -> Self {
+        NoiseWaveTable {
+            rnd: Cell::new(Rnd::new(seed)),
+            step: step.max(1),
+            held: Cell::new(0.0),
+        }
+    }
+}
+
+impl WaveTable for NoiseWaveTable {
+    #[inline(always)]
+    fn fill_buf<const FQ: u32>(
+        &self,
+        acc: Accumulator,
+        _dt: Time<FQ>,
+        _freq: f32,
+        buf: &mut [f32],
+        replace: f32,
+    ) -> Accumulator {
+        // Samples since last drawn value.
+        let mut n = acc.0 as usize;
 
-        // Fractional offset for the value wanted
-        let mut fract = acc.0;
+        let mut rnd = self.rnd.get();
+        let mut held = self.held.get();
 
         for b in buf {
-            fract += dp;
+            if n == 0 {
+                held = rnd.next_f32() * 2.0 - 1.0;
+            }
 
-            while fract > 1.0 {
-                fract -= 1.0;
+            n += 1;
+            if n >= self.step {
+                n = 0;
             }
 
-            let value = match self {
-                BasicWavetable::Saw => {
-                    //   /|
-                    //  / |
-                    // -  |
-                    //    | /
-                    //    |/
-
-                    let min_step = 1.0 / FQ as f32;
-
-                    // start at 0.0
-                    if fract < min_step {
-                        0.0
-                    } else if fract <= 0.5 {
-                        fract * 2.0
-                    } else {
-                        -1.0 + (fract - 0.5) * 2.0
-                    }
-                }
-                BasicWavetable::Square => {
-                    // ---|
-                    //    |
-                    //    |
-                    //    |
-                    //    |---
-                    if fract <= 0.5 {
-                        1.0
-                    } else {
-                        -1.0
-                    }
-                }
-                BasicWavetable::Sine => {
-                    let deg = fract * u32::MAX as f32;
-                    crate::geom::sin(deg as u32) as f32 / 32768.0
-                }
-                BasicWavetable::Triangle => {
-                    let deg = fract * u32::MAX as f32;
-                    crate::geom::tri(deg as u32) as f32 / 32768.0
-                }
-            };
+            let value = held;
 
             if replace == 0.0 {
                 // keep b.
@@ -306,13 +675,93 @@ impl WaveTable for BasicWavetable {
             }
         }
 
-        Accumulator(fract)
+        self.rnd.set(rnd);
+        self.held.set(held);
+
+        Accumulator(n as f32)
+    }
+
+    fn shift_phase(&self, acc: Accumulator, _phase: f32) -> Accumulator {
+        // Noise has no phase.
+        acc
+    }
+}
+
+/// Fill `buf` by advancing a fractional phase (0.0..1.0 for one oscillation) for each step.
+/// The `value` function is given the phase and the phase increment per step.
+#[inline(always)]
+fn fill_fract<const FQ: u32>(
+    acc: Accumulator,
+    dt: Time<FQ>,
+    freq: f32,
+    buf: &mut [f32],
+    replace: f32,
+    value: impl Fn(f32, f32) -> f32,
+) -> Accumulator {
+    // NB. dt.count is typically 1, so "as f32" is fine despite it being an i64
+    let dp = (dt.count as f32 * freq) / FQ as f32;
+
+    // Fractional offset for the value wanted
+    let mut fract = acc.0;
+
+    for b in buf {
+        fract += dp;
+
+        while fract > 1.0 {
+            fract -= 1.0;
+        }
+
+        let value = value(fract, dp);
+
+        if replace == 0.0 {
+            // keep b.
+        } else if replace == 1.0 {
+            *b = value;
+        } else {
+            // weight between existing and incoming value.
+            *b = *b + (value - *b) * replace;
+        }
+    }
+
+    Accumulator(fract)
+}
+
+/// Polynomial band-limited step. The correction to subtract from a naive
+/// waveform with a downwards step of 2.0 at `t = 0.0`. `dt` is the phase
+/// increment per sample, which is the width of the correction window.
+#[inline(always)]
+fn poly_blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        // Right after the discontinuity.
+        let t = t / dt;
+        2.0 * t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        // Right before the discontinuity.
+        let t = (t - 1.0) / dt;
+        t * t + 2.0 * t + 1.0
+    } else {
+        0.0
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::f32cmp::F32Cmp;
+
+    #[test]
+    fn test_wt_array_end() {
+        let mut buf = [0.0; 4];
+
+        let wt = ArrayWaveTable::new([0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        // 250Hz over 1000Hz moves exactly one element per sample, and the
+        // last sample lands exactly on the last element.
+        let acc = wt.fill_buf(Accumulator(0.0), Time::<1000>::new(1), 250.0, &mut buf, 1.0);
+
+        assert_eq!(buf, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(acc.0, 4.0);
+    }
 
     #[test]
     fn test_wt_saw() {
@@ -320,7 +769,7 @@ mod test {
 
         let wt = BasicWavetable::Saw;
 
-        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 0.0);
+        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 1.0);
 
         assert_eq!(
             buf,
@@ -331,13 +780,78 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_wt_blsaw() {
+        let mut buf1 = [0.0; 256];
+        let mut buf2 = [0.0; 256];
+
+        let dt = Time::<48_000>::new(1);
+
+        BasicWavetable::Saw.fill_buf(Accumulator(0.0), dt, 100.0, &mut buf1, 1.0);
+        BasicWavetable::BlSaw.fill_buf(Accumulator(0.0), dt, 100.0, &mut buf2, 1.0);
+
+        // Only the samples right next to the discontinuity are corrected.
+        let differing = buf1
+            .iter()
+            .zip(buf2.iter())
+            .filter(|(a, b)| (**a - **b).abs() > 0.001)
+            .count();
+
+        assert!(differing <= 2, "differing samples: {}", differing);
+    }
+
+    #[test]
+    fn test_wt_fn() {
+        let mut buf1 = [0.0; 16];
+        let mut buf2 = [0.0; 16];
+
+        let wt = FnWaveTable::new(|p| p * 2.0 - 1.0);
+        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf1, 1.0);
+
+        // Saw starts half way up the ramp.
+        let saw = BasicWavetable::Saw;
+        saw.fill_buf(Accumulator(0.5), Time::<64>::new(1), 440.0, &mut buf2, 1.0);
+
+        assert_eq!(buf1, buf2.map(F32Cmp));
+    }
+
+    #[test]
+    fn test_wt_noise() {
+        let mut buf = [0.0; 256];
+
+        let wt = NoiseWaveTable::new(42);
+        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 1.0);
+
+        assert!(buf.iter().all(|v| (-1.0..=1.0).contains(v)));
+        assert!(buf.windows(2).any(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn test_wt_noise_hold() {
+        let mut buf1 = [0.0; 6];
+        let mut buf2 = [0.0; 6];
+
+        let wt = NoiseWaveTable::with_step(42, 4);
+        let acc = wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf1, 1.0);
+        wt.fill_buf(acc, Time::<64>::new(1), 440.0, &mut buf2, 1.0);
+
+        let all: Vec<_> = buf1.iter().chain(buf2.iter()).collect();
+
+        // Held across the buffer boundary.
+        for chunk in all.chunks(4) {
+            assert!(chunk.iter().all(|v| *v == chunk[0]));
+        }
+        assert_ne!(all[0], all[4]);
+        assert_ne!(all[4], all[8]);
+    }
+
     #[test]
     fn test_wt_square() {
         let mut buf = [0.0; 16];
 
         let wt = BasicWavetable::Square;
 
-        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 0.0);
+        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 1.0);
 
         assert_eq!(
             buf,
@@ -354,7 +868,7 @@ mod test {
 
         let wt = BasicWavetable::Sine;
 
-        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 0.0);
+        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 1.0);
 
         assert_eq!(
             buf,
@@ -385,7 +899,7 @@ mod test {
 
         let wt = BasicWavetable::Triangle;
 
-        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 0.0);
+        wt.fill_buf(Accumulator(0.0), Time::<64>::new(1), 440.0, &mut buf, 1.0);
 
         assert_eq!(
             buf,
@@ -410,6 +924,146 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_params_from_note() {
+        let p = WaveTableParams::from_note(69, 0.0);
+        assert!((p.freq - 440.0).abs() < 0.01);
+
+        let p = WaveTableParams::from_note(81, 0.5);
+        assert!((p.freq - 880.0).abs() < 0.01);
+        assert_eq!(p.offset, 0.5);
+
+        let mut p = WaveTableParams::from_note(69, 0.0);
+        p.detune_cents(1200.0);
+        assert!((p.freq - 880.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_wt_buf_glide() {
+        // The phase as value means the difference between samples is the frequency.
+        let wt1 = FnWaveTable::new(|p| p);
+        let wt2 = FnWaveTable::new(|p| p);
+
+        let mut wt_buf = WaveTableBuffer::<_, _, 64, 48_000>::new(wt1, wt2);
+        wt_buf.set_glide_secs(0.01);
+
+        // Steady 440Hz to have a previous buffer.
+        wt_buf.advance_time();
+        let last = *wt_buf.buffer().last().unwrap();
+
+        let mut params = *wt_buf.params();
+        params.freq = 880.0;
+        wt_buf.set_params(params);
+
+        wt_buf.advance_time();
+
+        let mut prev = last;
+        let mut prev_diff = 440.0 / 48_000.0;
+
+        for v in wt_buf.buffer() {
+            let mut diff = v - prev;
+            if diff < 0.0 {
+                diff += 1.0;
+            }
+
+            assert!(diff > prev_diff, "{} > {}", diff, prev_diff);
+            assert!(diff < 880.0 / 48_000.0);
+
+            prev = *v;
+            prev_diff = diff;
+        }
+
+        assert!(wt_buf.freq() > 440.0 && wt_buf.freq() < 880.0);
+    }
+
+    #[test]
+    fn test_wt_buf_ring() {
+        use core::f32::consts::TAU;
+
+        let buffer = |offset: f32, combine: CombineMode| {
+            let wt1 = FnWaveTable::new(|p| (p * TAU).sin());
+            let wt2 = FnWaveTable::new(|p| (p * 3.0 * TAU).sin());
+
+            let mut wt_buf = WaveTableBuffer::<_, _, 64, 48_000>::new(wt1, wt2);
+            wt_buf.set_combine(combine);
+
+            let mut params = *wt_buf.params();
+            params.offset = offset;
+            wt_buf.set_params(params);
+
+            // First buffer morphs to the parameters.
+            wt_buf.advance_time();
+            wt_buf.advance_time();
+
+            *wt_buf.buffer().first_chunk::<64>().unwrap()
+        };
+
+        let sine1 = buffer(0.0, CombineMode::Blend);
+        let sine2 = buffer(1.0, CombineMode::Blend);
+        let ring = buffer(1.0, CombineMode::Ring);
+
+        for i in 0..64 {
+            assert!((ring[i] - sine1[i] * sine2[i]).abs() < 0.0001);
+        }
+
+        assert_ne!(ring, sine1);
+        assert_ne!(ring, sine2);
+    }
+
+    #[test]
+    fn test_wt_buf_stereo() {
+        let mut wt_buf =
+            WaveTableBuffer::<_, _, 64, 48_000>::new(BasicWavetable::Sine, BasicWavetable::Sine);
+        wt_buf.set_stereo_phase(Some(0.25));
+
+        // One oscillation per buffer.
+        let mut params = *wt_buf.params();
+        params.freq = 750.0;
+        wt_buf.set_params(params);
+
+        wt_buf.advance_time();
+        wt_buf.advance_time();
+
+        let (left, right) = (wt_buf.buffer(), wt_buf.buffer_right());
+
+        let corr = |lag: usize| -> f32 { (0..64).map(|i| left[(i + lag) % 64] * right[i]).sum() };
+
+        let peak = (0..64)
+            .max_by(|a, b| corr(*a).partial_cmp(&corr(*b)).unwrap())
+            .unwrap();
+
+        // Right is a quarter cycle ahead of left.
+        assert_eq!(peak, 16);
+
+        wt_buf.set_stereo_phase(None);
+        assert_eq!(wt_buf.buffer(), wt_buf.buffer_right());
+    }
+
+    #[test]
+    fn test_wt_buf_sample() {
+        use crate::f32cmp::F32Cmp;
+
+        let new = || {
+            let mut wt_buf =
+                WaveTableBuffer::<_, _, 64, 48_000>::new(BasicWavetable::Saw, BasicWavetable::Sine);
+            wt_buf.set_params(WaveTableParams {
+                offset: 0.3,
+                freq: 1000.0,
+            });
+            wt_buf.advance_time();
+            wt_buf
+        };
+
+        let mut wt_buf1 = new();
+        let mut wt_buf2 = new();
+
+        wt_buf1.advance_time();
+
+        for v in wt_buf1.buffer() {
+            assert_eq!(F32Cmp(wt_buf2.sample()), *v);
+        }
+    }
+
     // #[test]
     // fn test_wt_buf() {
     //     let wt1 = BasicWavetable::Saw;